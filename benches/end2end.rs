@@ -357,6 +357,25 @@ fn verify_benchmark(c: &mut Criterion) {
         });
     }
 
+    // short computations like this one fold in a single step
+    let benchmark_id = BenchmarkId::new("verify_single_step_nova", "_(+ 1 2)");
+    group.bench_function(benchmark_id, |b| {
+        let ptr = store.read(state.clone(), "(+ 1 2)").unwrap();
+        let prover = NovaProver::new(reduction_count, lang_pallas_rc.clone());
+        let frames = evaluate::<Fq, Coproc<Fq>>(None, ptr, &store, limit).unwrap();
+        let (proof, z0, zi, num_steps) = prover.prove(&pp, &frames, &store).unwrap();
+        assert_eq!(num_steps, 1);
+
+        b.iter_batched(
+            || z0.clone(),
+            |z0| {
+                let result = proof.verify(&pp, &z0, &zi[..]).unwrap();
+                black_box(result);
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
     group.finish();
 }
 
@@ -0,0 +1,53 @@
+use pasta_curves::pallas::Scalar as Fq;
+use std::sync::Arc;
+
+use lurk::{
+    eval::lang::{Coproc, Lang},
+    lem::{eval::evaluate, store::Store},
+    proof::{latency::bench_verify, nova::NovaProver, Prover, RecursiveSNARKTrait},
+    public_parameters::{
+        self,
+        instance::{Instance, Kind},
+    },
+    state::State,
+};
+
+mod common;
+use common::set_bench_config;
+
+const DEFAULT_REDUCTION_COUNT: usize = 10;
+const ITERATIONS: usize = 100;
+
+/// Reports the distribution of the verification latency of a single step Nova
+/// proof, before and after compression, for capacity planning of verifiers.
+///
+/// To run this benchmark, do `cargo bench --bench verify_latency --features bench-utils`.
+fn main() {
+    set_bench_config();
+    let limit = 1_000_000_000;
+    let store = Store::default();
+    let reduction_count = DEFAULT_REDUCTION_COUNT;
+    let state = State::init_lurk_state().rccell();
+    let lang_pallas_rc = Arc::new(Lang::<Fq, Coproc<Fq>>::new());
+
+    // use cached public params
+    let instance = Instance::new(
+        reduction_count,
+        lang_pallas_rc.clone(),
+        true,
+        Kind::NovaPublicParams,
+    );
+    let pp = public_parameters::public_params(&instance).unwrap();
+
+    let ptr = store.read(state, "(+ 1 2)").unwrap();
+    let prover = NovaProver::new(reduction_count, lang_pallas_rc);
+    let frames = evaluate::<Fq, Coproc<Fq>>(None, ptr, &store, limit).unwrap();
+    let (proof, z0, zi, _num_steps) = prover.prove(&pp, &frames, &store).unwrap();
+
+    let stats = bench_verify(&*pp, &proof, &z0, &zi, ITERATIONS).unwrap();
+    println!("verify_single_step_nova: {stats:?}");
+
+    let compressed_proof = proof.compress(&pp).unwrap();
+    let stats = bench_verify(&*pp, &compressed_proof, &z0, &zi, ITERATIONS).unwrap();
+    println!("verify_compressed_single_step_nova: {stats:?}");
+}
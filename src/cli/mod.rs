@@ -6,7 +6,7 @@ mod field_data;
 mod lurk_proof;
 pub mod paths;
 mod repl;
-mod zstore;
+pub(crate) mod zstore;
 
 use anyhow::{bail, Context, Result};
 use camino::Utf8PathBuf;
@@ -15,6 +15,9 @@ pub static LURK_CONFIG: OnceCell<Settings> = OnceCell::new();
 /// Global path variable for configuration file
 pub static LURK_CONFIG_FILE: OnceCell<Utf8PathBuf> = OnceCell::new();
 
+/// Default value for the `max_coprocessors` setting
+pub const DEFAULT_MAX_COPROCESSORS: usize = 64;
+
 /// Gets the `LURK_CONFIG` settings. If uninitialized, sets the global variable
 /// in the following order (greatest to least precedence):
 /// - `settings` map if provided, e.g. with key ("public_params_dir", "$HOME/lurk-rs/public_params")
@@ -26,6 +29,9 @@ pub static LURK_CONFIG_FILE: OnceCell<Utf8PathBuf> = OnceCell::new();
 ///   ```
 ///   Other file formats are supported by the `config` crate, but only TOML is tested
 /// - Default values, e.g. `$HOME/.lurk/public_params`
+///
+/// Initializing it reads the environment and the config file, so hosts without
+/// a filesystem should set `LURK_CONFIG` first, e.g. to `Settings::in_memory()`.
 pub fn lurk_config(
     file: Option<&Utf8PathBuf>,
     settings: Option<&HashMap<&str, String>>,
@@ -60,6 +66,25 @@ pub struct Settings {
 
     /// Parallelism & witness gen configs
     pub perf: PerfConfig,
+
+    /// Maximum number of coprocessors of a `Lang` for which NIVC public
+    /// parameters can be set up, since each one adds a circuit to the setup
+    pub max_coprocessors: usize,
+
+    /// Whether witnesses cached while proving should be wiped once folded, for
+    /// computations whose witnesses encode private inputs
+    pub zeroize_witnesses: bool,
+
+    /// Maximum number of bytes of witnesses cached ahead of folding when
+    /// proving in parallel. Once reached, the remaining witnesses are computed
+    /// on demand during folding. Unbounded if unset
+    pub witness_cache_limit_bytes: Option<usize>,
+
+    /// Soft memory budget for proving: while the memory available on the host
+    /// is below this many bytes, witnesses are no longer cached ahead of
+    /// folding and folded witnesses are dropped right away, trading speed for
+    /// memory. See `proof::under_memory_pressure`. Disabled if unset
+    pub min_available_memory_bytes: Option<usize>,
 }
 
 impl Settings {
@@ -76,6 +101,8 @@ impl Settings {
             // Default settings if unspecified in the config file
             .set_default(public_params, public_params_default_dir().to_string())?
             .set_default("perf", "max-parallel-simple".to_string())?
+            .set_default("max_coprocessors", DEFAULT_MAX_COPROCESSORS as u64)?
+            .set_default("zeroize_witnesses", false)?
             .add_source(File::with_name(config_file.as_str()).required(false))
             // Then override with any `LURK` environment variables
             .add_source(Environment::with_prefix("LURK"))
@@ -86,11 +113,29 @@ impl Settings {
     }
 }
 
+impl Settings {
+    /// The default settings, except for an empty `public_params_dir`, built
+    /// without reading the environment or the filesystem. Setting `LURK_CONFIG`
+    /// to these before any other use lets proving and verifying run entirely in
+    /// memory, as long as the public parameters aren't loaded from the disk
+    /// cache. See "Running in memory" in the `proof` module docs.
+    pub fn in_memory() -> Self {
+        Self {
+            public_params_dir: Utf8PathBuf::new(),
+            perf: PerfConfig::default(),
+            max_coprocessors: DEFAULT_MAX_COPROCESSORS,
+            zeroize_witnesses: false,
+            witness_cache_limit_bytes: None,
+            min_available_memory_bytes: None,
+        }
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
             public_params_dir: public_params_default_dir(),
-            perf: PerfConfig::default(),
+            ..Self::in_memory()
         }
     }
 }
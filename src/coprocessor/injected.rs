@@ -0,0 +1,212 @@
+//! Coprocessors driven by results computed out-of-band.
+//!
+//! Some coprocessors compute something expensive, e.g. they stand for a call to
+//! an external service. [`Injected`] wraps such a coprocessor so its results can
+//! be computed ahead of time and injected when the frames are generated, instead
+//! of being computed by the evaluator. The frames are then proven as usual.
+//!
+//! # Trust model
+//! Injected results aren't trusted: the circuit of the wrapped coprocessor is
+//! synthesized as it is, so it must constrain its result to be the right one
+//! for its arguments, exactly as without injection. A wrong injected result
+//! makes the evaluation disagree with the circuit, and the proof fails to
+//! verify. Injection thus only saves the computation done by the evaluator,
+//! and not the one done when synthesizing the circuit.
+
+use bellpepper::gadgets::boolean::Boolean;
+use bellpepper_core::{ConstraintSystem, SynthesisError};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    circuit::gadgets::pointer::AllocatedPtr,
+    field::LurkField,
+    lem::{
+        circuit::GlobalAllocator,
+        pointers::{Ptr, ZPtr},
+        store::Store,
+    },
+};
+
+use super::{CoCircuit, Coprocessor};
+
+/// A coprocessor whose results can be injected ahead of evaluation. Calls with
+/// arguments for which no result was injected are evaluated by the wrapped
+/// coprocessor.
+///
+/// Clones share their injected results, so results injected through a handle
+/// kept by the caller are seen by the copy held by a `Lang`. The results are
+/// `Ptr`s of the store the frames are generated with, and aren't serialized.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(serialize = "C: Serialize", deserialize = "C: Deserialize<'de>"))]
+pub struct Injected<F: LurkField, C> {
+    inner: C,
+    #[serde(skip)]
+    results: Arc<RwLock<HashMap<Vec<ZPtr<F>>, Ptr>>>,
+}
+
+impl<F: LurkField, C> Injected<F, C> {
+    /// Wraps `inner`, without any injected result yet
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            results: Default::default(),
+        }
+    }
+
+    /// The wrapped coprocessor
+    #[inline]
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Injects `result` as the result of the call with the arguments `args`,
+    /// replacing any result previously injected for them
+    pub fn inject(&self, store: &Store<F>, args: &[Ptr], result: Ptr) {
+        let key = args.iter().map(|arg| store.hash_ptr(arg)).collect();
+        self.results.write().unwrap().insert(key, result);
+    }
+
+    /// The result injected for the call with the arguments `args`, if any
+    pub fn injected(&self, store: &Store<F>, args: &[Ptr]) -> Option<Ptr> {
+        let key: Vec<_> = args.iter().map(|arg| store.hash_ptr(arg)).collect();
+        self.results.read().unwrap().get(&key).copied()
+    }
+}
+
+impl<F: LurkField, C: Coprocessor<F>> Coprocessor<F> for Injected<F, C> {
+    fn eval_arity(&self) -> usize {
+        self.inner.eval_arity()
+    }
+
+    fn has_circuit(&self) -> bool {
+        self.inner.has_circuit()
+    }
+
+    fn evaluate(&self, s: &Store<F>, args: &[Ptr], env: &Ptr, cont: &Ptr) -> Vec<Ptr> {
+        match self.injected(s, args) {
+            Some(result) => vec![result, *env, *cont],
+            None => self.inner.evaluate(s, args, env, cont),
+        }
+    }
+
+    fn evaluate_simple(&self, s: &Store<F>, args: &[Ptr]) -> Ptr {
+        self.injected(s, args)
+            .unwrap_or_else(|| self.inner.evaluate_simple(s, args))
+    }
+}
+
+impl<F: LurkField, C: CoCircuit<F>> CoCircuit<F> for Injected<F, C> {
+    fn arity(&self) -> usize {
+        self.inner.arity()
+    }
+
+    fn synthesize_internal<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        g: &GlobalAllocator<F>,
+        s: &Store<F>,
+        not_dummy: &Boolean,
+        ptrs: &[AllocatedPtr<F>],
+    ) -> Result<Vec<AllocatedPtr<F>>, SynthesisError> {
+        self.inner.synthesize_internal(cs, g, s, not_dummy, ptrs)
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        g: &GlobalAllocator<F>,
+        s: &Store<F>,
+        not_dummy: &Boolean,
+        args: &[AllocatedPtr<F>],
+        env: &AllocatedPtr<F>,
+        cont: &AllocatedPtr<F>,
+    ) -> Result<Vec<AllocatedPtr<F>>, SynthesisError> {
+        self.inner.synthesize(cs, g, s, not_dummy, args, env, cont)
+    }
+
+    fn synthesize_simple<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        g: &GlobalAllocator<F>,
+        s: &Store<F>,
+        not_dummy: &Boolean,
+        args: &[AllocatedPtr<F>],
+    ) -> Result<AllocatedPtr<F>, SynthesisError> {
+        self.inner.synthesize_simple(cs, g, s, not_dummy, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper::util_cs::witness_cs::WitnessCS;
+    use bellpepper_core::num::AllocatedNum;
+    use nova::traits::circuit::StepCircuit;
+    use pasta_curves::Fq;
+
+    use super::*;
+    use crate::{
+        coprocessor::test::DumbCoprocessor,
+        eval::lang::Lang,
+        lem::{eval::EvalConfig, multiframe::MultiFrame},
+        proof::supernova::FoldingConfig,
+        state::user_sym,
+    };
+
+    #[test]
+    fn test_injected() {
+        let store = Store::<Fq>::default();
+        let cproc = Injected::new(DumbCoprocessor::new());
+        let mut lang = Lang::<Fq, Injected<Fq, DumbCoprocessor<Fq>>>::new();
+        lang.add_coprocessor(user_sym("cproc-dumb"), cproc.clone());
+        let lang = Arc::new(lang);
+        let ec = EvalConfig::new_ivc(&lang);
+        let expr = store.read_with_default_state("(cproc-dumb 3 4)").unwrap();
+        let output = |expr| {
+            let frames =
+                MultiFrame::build_frames(expr, store.intern_empty_env(), &store, 10, &ec).unwrap();
+            frames.last().unwrap().output.clone()
+        };
+
+        // without injection, the wrapped coprocessor computes 3^2 + 4
+        assert_eq!(output(expr)[0], store.num_u64(13));
+
+        // the handle injects into the copy held by the `Lang`
+        let args = [store.num_u64(3), store.num_u64(4)];
+        cproc.inject(&store, &args, store.num_u64(14));
+        let frames =
+            MultiFrame::build_frames(expr, store.intern_empty_env(), &store, 10, &ec).unwrap();
+        let injected_output = frames.last().unwrap().output.clone();
+        assert_eq!(injected_output[0], store.num_u64(14));
+
+        // other arguments are still evaluated by the wrapped coprocessor
+        let other = store.read_with_default_state("(cproc-dumb 1 4)").unwrap();
+        assert_eq!(output(other)[0], store.num_u64(5));
+
+        // but the circuit still computes the right result from the input
+        let folding_config = Arc::new(FoldingConfig::new_ivc(lang.clone(), 10));
+        let steps = MultiFrame::from_frames(&frames, &store, &folding_config);
+        assert_eq!(steps.len(), 1);
+        store.hydrate_z_cache();
+        let mut cs = WitnessCS::<Fq>::new();
+        let z = store
+            .to_scalar_vector(&frames[0].input)
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| AllocatedNum::alloc_infallible(cs.namespace(|| format!("z{i}")), || x))
+            .collect::<Vec<_>>();
+        let circuit_output = StepCircuit::synthesize(&steps[0], &mut cs, &z).unwrap();
+        let circuit_output = circuit_output
+            .iter()
+            .map(|x| x.get_value().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            circuit_output[..2],
+            store.to_scalar_vector(&[store.num_u64(13)])
+        );
+        assert_ne!(circuit_output, store.to_scalar_vector(&injected_output));
+    }
+}
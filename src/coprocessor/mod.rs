@@ -10,6 +10,7 @@ use crate::{
 
 pub mod circom;
 pub mod gadgets;
+pub mod injected;
 pub mod memoset;
 pub mod sha256;
 pub mod trie;
@@ -57,6 +58,14 @@ pub trait Coprocessor<F: LurkField>: Clone + Debug + Sync + Send + CoCircuit<F>
 /// generalizes over functionality needed in the evaluator.
 ///
 /// The trait is implemented by concrete coprocessor types, such as `DumbCoprocessor`.
+///
+/// # Synthesis depth
+/// Lurk doesn't bound the depth of circuit synthesis. It doesn't need to for its own
+/// circuits, since LEM functions own the functions they call (`Op::Call` holds a
+/// `Box<Func>`), so they're finite trees that can't recurse. `synthesize` is called once
+/// per coprocessor call site and plain Rust, so the depth of its own recursion can't be
+/// observed from the outside: a coprocessor must bound it itself, and one that recurses
+/// without bound overflows the stack during setup as during witness generation.
 pub trait CoCircuit<F: LurkField>: Send + Sync + Clone {
     fn arity(&self) -> usize {
         todo!()
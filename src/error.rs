@@ -15,6 +15,89 @@ pub enum ProofError {
     Synthesis(#[from] SynthesisError),
     #[error("Reduction error: {0}")]
     Reduction(#[from] ReductionError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
+    #[error("Invalid Lang: {0}")]
+    Lang(#[from] LangError),
+    #[error("No steps to prove")]
+    NoSteps,
+    #[error("The steps take an input of {expected} elements, got {got}")]
+    InputArityMismatch { expected: usize, got: usize },
+    #[error("Too many circuits: {count} coprocessors exceed the maximum of {max}")]
+    TooManyCircuits { count: usize, max: usize },
+    #[error("No reduction count fits {target} constraints per step, the minimum is {min}")]
+    ConstraintTargetTooLow { target: usize, min: usize },
+    #[error("Public parameters for reduction count {params_rc} can't fold steps of reduction count {rc}")]
+    ReductionCountMismatch { params_rc: usize, rc: usize },
+    #[error("Public parameters for {expected:?} folding can't verify a proof folded with {got:?}")]
+    FoldingModeMismatch {
+        expected: crate::proof::FoldingMode,
+        got: crate::proof::FoldingMode,
+    },
+    #[error("Expected {expected} circuits, got {got}")]
+    CircuitCountMismatch { expected: usize, got: usize },
+    #[error("Step {step_index} claims a program counter its circuit doesn't encode")]
+    PcInconsistency { step_index: usize },
+    #[error("Step {step_index} is folded by circuit {pc}, which isn't allowed")]
+    DisallowedCoprocessor { pc: usize, step_index: usize },
+    #[error("Step {step_index} was built for another Lang")]
+    LangDigestMismatch { step_index: usize },
+    #[error("Expected parameters for the Lang {expected:?}, got {got:?}")]
+    LangKeyMismatch { expected: String, got: String },
+    #[error("The proof doesn't attest an output")]
+    NoVerifiedOutput,
+    #[error("Step {step_index} has no output")]
+    MissingOutput { step_index: usize },
+    #[error("Chunk {index} doesn't start at the output of the previous chunk")]
+    ChunkInputMismatch { index: usize },
+    #[error("Chunk {index} failed to verify")]
+    InvalidChunk { index: usize },
+    #[error("Evaluation didn't terminate within {limit} reductions")]
+    EvaluationLimitExceeded { limit: usize },
+    #[error("Initial environment isn't a well-formed environment")]
+    InvalidInitialEnv,
+    #[error("Initial continuation isn't a continuation")]
+    InvalidInitialCont,
+    #[error("Proof failed to verify")]
+    VerificationFailed,
+    #[error("Expected a compressed proof")]
+    NotCompressed,
+    #[error("Expected a recursive proof, found a compressed one")]
+    AlreadyCompressed,
+    #[error("The state transition doesn't evaluate to a state commitment")]
+    InvalidStateTransition,
+    #[error("The appended steps don't start at the output of the proof")]
+    NonContiguousComposition,
+    #[error("The auxiliary parameters weren't generated along with the circuit shapes")]
+    AuxParamsMismatch,
+    #[error("The checkpoint was made with other public parameters")]
+    CheckpointParamsMismatch,
+    #[error("Checkpoints must be at least one step apart")]
+    ZeroCheckpointInterval,
+    #[error("The witness of step {step_index} has {size} elements, more than allowed")]
+    WitnessTooLarge { step_index: usize, size: usize },
+    #[error("Invalid hex field element: {0:?}")]
+    InvalidFieldElement(String),
+    #[error("Expected an input and an output of {expected} elements, got {z0} and {zi}")]
+    ArityMismatch {
+        expected: usize,
+        z0: usize,
+        zi: usize,
+    },
+}
+
+impl ProofError {
+    /// The `SuperNovaError` this error originates from, if any, which is also
+    /// its `std::error::Error::source`
+    #[inline]
+    pub fn supernova_source(&self) -> Option<&SuperNovaError> {
+        match self {
+            Self::SuperNova(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<store::Error> for ProofError {
@@ -30,3 +113,21 @@ pub enum ReductionError {
     #[error("Lookup error: {0}")]
     Store(#[from] store::Error),
 }
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LangError {
+    #[error("Coprocessor {name} is mapped to circuit index {found:?} instead of {expected}")]
+    CircuitIndexMismatch {
+        name: String,
+        expected: usize,
+        found: Option<usize>,
+    },
+    #[error(
+        "Coprocessor {name} evaluates {eval_arity} arguments but its circuit takes {circuit_arity}"
+    )]
+    CircuitArityMismatch {
+        name: String,
+        eval_arity: usize,
+        circuit_arity: usize,
+    },
+}
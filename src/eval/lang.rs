@@ -1,13 +1,15 @@
 use indexmap::IndexMap;
 use lurk_macros::Coproc;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Debug, marker::PhantomData};
+use std::{collections::BTreeMap, fmt::Debug, marker::PhantomData, sync::Arc};
 
 use crate::{
     self as lurk,
     coprocessor::{CoCircuit, Coprocessor},
+    error::LangError,
     field::LurkField,
-    lem::{pointers::Ptr, store::Store},
+    lem::{pointers::Ptr, store::Store, Func},
     symbol::Symbol,
 };
 
@@ -76,6 +78,19 @@ pub enum Coproc<F: LurkField> {
 pub struct Lang<F, C> {
     /// An IndexMap that stores coprocessors with their associated `Sym` keys.
     coprocessors: IndexMap<Symbol, C>,
+    /// The digests of the blank NIVC circuits, computed once on first use and
+    /// reset when coprocessors are added
+    #[serde(skip)]
+    circuit_digests: OnceCell<Vec<F>>,
+    /// The LEM functions of the blank circuits, built once on first use and
+    /// reset when coprocessors are added. Provers sharing an `Arc<Lang>` thus
+    /// share them too, instead of rebuilding them for every blank `MultiFrame`.
+    #[serde(skip)]
+    ivc_step: OnceCell<Arc<Func>>,
+    #[serde(skip)]
+    nivc_step: OnceCell<Arc<Func>>,
+    #[serde(skip)]
+    cproc_funcs: OnceCell<Arc<[Func]>>,
     _p: PhantomData<F>,
 }
 
@@ -84,6 +99,10 @@ impl<F: LurkField, C> Lang<F, C> {
     pub fn new() -> Self {
         Self {
             coprocessors: IndexMap::default(),
+            circuit_digests: OnceCell::new(),
+            ivc_step: OnceCell::new(),
+            nivc_step: OnceCell::new(),
+            cproc_funcs: OnceCell::new(),
             _p: PhantomData,
         }
     }
@@ -113,6 +132,10 @@ impl<F: LurkField, C> Lang<F, C> {
     pub fn add_coprocessor<T: Into<C>, S: Into<Symbol>>(&mut self, name: S, cproc: T) {
         let name = name.into();
         self.coprocessors.insert(name, cproc.into());
+        self.circuit_digests.take();
+        self.ivc_step.take();
+        self.nivc_step.take();
+        self.cproc_funcs.take();
     }
 
     pub fn add_binding<B: Into<Binding<F, C>>>(&mut self, binding: B) {
@@ -120,6 +143,29 @@ impl<F: LurkField, C> Lang<F, C> {
         self.add_coprocessor(name, coproc);
     }
 
+    /// Returns the digests of the blank NIVC circuits of this `Lang`, computing
+    /// them with `digests` if they haven't been computed yet
+    pub(crate) fn circuit_digests_with(&self, digests: impl FnOnce() -> Vec<F>) -> &[F] {
+        self.circuit_digests.get_or_init(digests)
+    }
+
+    /// Returns the step function of the circuits of this `Lang` for IVC or for
+    /// NIVC, building it with `step` if it hasn't been built yet
+    pub(crate) fn step_func_with(&self, nivc: bool, step: impl FnOnce() -> Func) -> Arc<Func> {
+        let cell = if nivc {
+            &self.nivc_step
+        } else {
+            &self.ivc_step
+        };
+        cell.get_or_init(|| Arc::new(step())).clone()
+    }
+
+    /// Returns the functions of the NIVC coprocessor circuits of this `Lang`,
+    /// building them with `funcs` if they haven't been built yet
+    pub(crate) fn cproc_funcs_with(&self, funcs: impl FnOnce() -> Vec<Func>) -> Arc<[Func]> {
+        self.cproc_funcs.get_or_init(|| funcs().into()).clone()
+    }
+
     #[inline]
     pub fn coprocessors(&self) -> &IndexMap<Symbol, C> {
         &self.coprocessors
@@ -149,6 +195,48 @@ impl<F: LurkField, C> Lang<F, C> {
     pub fn get_index_by_symbol(&self, sym: &Symbol) -> Option<usize> {
         self.coprocessors.get_index_of(sym)
     }
+
+    /// The names of the coprocessors whose circuit never appears in
+    /// `pc_histogram`, which counts folded steps per program counter, as in
+    /// `ProofStats::pc_histogram`. In a representative workload, these are the
+    /// coprocessors that could be pruned from the `Lang`, sparing the setup of
+    /// their circuits. The program counter of the `i`-th coprocessor is `i + 1`.
+    pub fn unused_coprocessors(&self, pc_histogram: &BTreeMap<usize, usize>) -> Vec<String> {
+        self.coprocessors
+            .keys()
+            .enumerate()
+            .filter(|(i, _)| pc_histogram.get(&(i + 1)).map_or(true, |&n| n == 0))
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+}
+
+impl<F: LurkField, C: Coprocessor<F>> Lang<F, C> {
+    /// Checks that the coprocessors can be dispatched to their NIVC circuits:
+    /// the circuit index of each coprocessor must be its position in the `Lang`,
+    /// which program counters are derived from, and its evaluation arity must
+    /// match the arity of its circuit. Errors with the offending coprocessor.
+    pub fn validate_circuit_mapping(&self) -> Result<(), LangError> {
+        for (expected, (name, cproc)) in self.coprocessors.iter().enumerate() {
+            let found = self.get_index_by_symbol(name);
+            if found != Some(expected) {
+                return Err(LangError::CircuitIndexMismatch {
+                    name: name.to_string(),
+                    expected,
+                    found,
+                });
+            }
+            let (eval_arity, circuit_arity) = (cproc.eval_arity(), cproc.arity());
+            if eval_arity != circuit_arity {
+                return Err(LangError::CircuitArityMismatch {
+                    name: name.to_string(),
+                    eval_arity,
+                    circuit_arity,
+                });
+            }
+        }
+        Ok(())
+    }
 }
 
 /// A `Binding` associates a name (`Sym`) and `Coprocessor`. It facilitates modular construction of `Lang`s using
@@ -188,6 +276,25 @@ pub(crate) mod test {
         Lang::<Fr, Coproc<Fr>>::new();
     }
 
+    #[test]
+    fn unused_coprocessors() {
+        let (dummy, other) = (sym!("coproc", "dummy"), sym!("coproc", "other"));
+        let lang = Lang::<Fr, Coproc<Fr>>::new_with_bindings(vec![
+            (dummy.clone(), DummyCoprocessor::new().into()),
+            (other.clone(), DummyCoprocessor::new().into()),
+        ]);
+        let all = vec![dummy.to_string(), other.to_string()];
+        assert_eq!(lang.unused_coprocessors(&BTreeMap::new()), all);
+        assert_eq!(lang.unused_coprocessors(&[(0, 5)].into()), all);
+        assert_eq!(
+            lang.unused_coprocessors(&[(0, 5), (2, 1), (1, 0)].into()),
+            vec![dummy.to_string()]
+        );
+        assert!(lang
+            .unused_coprocessors(&[(1, 1), (2, 1)].into())
+            .is_empty());
+    }
+
     #[test]
     fn dummy_lang() {
         let _lang = Lang::<Fr, Coproc<Fr>>::new_with_bindings(vec![(
@@ -99,6 +99,27 @@ pub trait LurkField: PrimeField + PrimeFieldBits {
         res.to_owned()
     }
 
+    /// Attempts to parse a field element from hexadecimal digits, as output by
+    /// `hex_digits` or `trimmed_hex_digits`, optionally prefixed with `0x`.
+    /// Returns `None` if the digits aren't hexadecimal or don't encode an
+    /// element of the field.
+    fn from_hex_digits(digits: &str) -> Option<Self> {
+        let digits = digits.strip_prefix("0x").unwrap_or(digits);
+        let num_bytes = Self::default().to_repr().as_ref().len();
+        if digits.is_empty()
+            || digits.len() > 2 * num_bytes
+            || !digits.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            return None;
+        }
+        let padded = format!("{digits:0>width$}", width = 2 * num_bytes);
+        let mut bytes = (0..num_bytes)
+            .map(|i| u8::from_str_radix(&padded[2 * i..2 * i + 2], 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+        bytes.reverse();
+        Self::from_bytes(&bytes)
+    }
+
     /// Attempts to convert the field element to a u16
     fn to_u16(&self) -> Option<u16> {
         for x in &self.to_repr().as_ref()[2..] {
@@ -442,6 +463,26 @@ pub mod tests {
       }
     }
 
+    proptest! {
+      #[test]
+      fn prop_hex_digits_roundtrip(x in any::<FWrap<Fr>>()) {
+          assert_eq!(Fr::from_hex_digits(&x.0.hex_digits()), Some(x.0));
+          assert_eq!(Fr::from_hex_digits(&x.0.trimmed_hex_digits()), Some(x.0));
+          let prefixed = format!("0x{}", x.0.trimmed_hex_digits());
+          assert_eq!(Fr::from_hex_digits(&prefixed), Some(x.0));
+      }
+    }
+
+    #[test]
+    fn test_invalid_hex_digits() {
+        assert_eq!(Fr::from_hex_digits(""), None);
+        assert_eq!(Fr::from_hex_digits("0x"), None);
+        assert_eq!(Fr::from_hex_digits("12g4"), None);
+        assert_eq!(Fr::from_hex_digits(&"1".repeat(65)), None);
+        // the modulus itself isn't a field element
+        assert_eq!(Fr::from_hex_digits(&"f".repeat(64)), None);
+    }
+
     // This checks that the field we're using have a representation
     // such that forall x: u64, F::from(x).to_repr() == x.to_le_bytes()
     // This enables a fast conversion for tags, and must be present for all fields
@@ -70,6 +70,7 @@ mod slot;
 pub mod store;
 pub mod tag;
 mod var_map;
+mod witness_cs;
 
 use anyhow::{bail, Result};
 use indexmap::IndexMap;
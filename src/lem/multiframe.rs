@@ -7,7 +7,10 @@ use ff::PrimeField;
 use nova::{supernova::NonUniformCircuit, traits::Engine};
 use once_cell::sync::OnceCell;
 use rayon::prelude::*;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 use crate::{
     circuit::gadgets::pointer::AllocatedPtr,
@@ -19,7 +22,7 @@ use crate::{
     proof::{
         nova::{CurveCycleEquipped, E1, E2},
         supernova::{FoldingConfig, C2},
-        CEKState, EvaluationStore, FrameLike, Provable,
+        CEKState, EvaluationStore, FrameLike, Provable, ProvingSettings,
     },
     tag::ContTag,
 };
@@ -34,6 +37,7 @@ use super::{
     pointers::Ptr,
     slot::SlotType,
     store::Store,
+    witness_cs::{zeroize_scalars, ZeroizingWitnessCS},
     Func, Tag,
 };
 
@@ -49,13 +53,33 @@ pub struct MultiFrame<'a, F: LurkField, C: Coprocessor<F>> {
     output: Option<Vec<Ptr>>,
     frames: Option<Vec<Frame>>,
     /// Cached witness and output for this `MultiFrame`
-    cached_witness: OnceCell<(WitnessCS<F>, Vec<AllocatedNum<F>>)>,
+    cached_witness: OnceCell<(ZeroizingWitnessCS<F>, Vec<AllocatedNum<F>>)>,
     num_frames: usize,
     folding_config: Arc<FoldingConfig<F, C>>,
     pc: usize,
     next_pc: usize,
 }
 
+/// The step function of the IVC circuit of `lang`, built once per `Lang`
+fn ivc_step<F: LurkField, C: Coprocessor<F>>(lang: &Lang<F, C>) -> Arc<Func> {
+    lang.step_func_with(false, || {
+        make_eval_step_from_config(&EvalConfig::new_ivc(lang))
+    })
+}
+
+/// The step function of the NIVC circuits of `lang`, built once per `Lang`
+fn nivc_step<F: LurkField, C: Coprocessor<F>>(lang: &Lang<F, C>) -> Arc<Func> {
+    lang.step_func_with(true, || {
+        make_eval_step_from_config(&EvalConfig::new_nivc(lang))
+    })
+}
+
+/// The functions of the NIVC coprocessor circuits of `lang`, built once per
+/// `Lang`
+fn cproc_funcs<F: LurkField, C: Coprocessor<F>>(lang: &Lang<F, C>) -> Arc<[Func]> {
+    lang.cproc_funcs_with(|| make_cprocs_funcs_from_lang(lang))
+}
+
 impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
     fn get_func(&self) -> &Func {
         if self.pc == 0 {
@@ -66,7 +90,7 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
     }
 
     #[inline]
-    fn get_lang(&self) -> &Arc<Lang<F, C>> {
+    pub fn get_lang(&self) -> &Arc<Lang<F, C>> {
         self.folding_config.lang()
     }
 
@@ -75,6 +99,11 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
         self.frames.as_ref()
     }
 
+    #[inline]
+    pub fn input(&self) -> &Option<Vec<Ptr>> {
+        &self.input
+    }
+
     #[inline]
     pub fn output(&self) -> &Option<Vec<Ptr>> {
         &self.output
@@ -84,25 +113,174 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
         eval_frame.emitted.clone()
     }
 
+    /// Computes and caches the witness of the frame, to be used when folding it.
+    ///
+    /// The witness buffers are allocated by `ZeroizingWitnessCS` and copied by
+    /// the folding scheme, neither of which is generic over an allocator, so
+    /// they can't be routed to an arena or a pool from here. Deployments bound
+    /// by allocations can swap the `#[global_allocator]` of their binary
+    /// instead.
     pub fn cache_witness(&mut self, s: &Store<F>) -> Result<(), SynthesisError> {
+        self.cache_witness_with(s, lurk_config(None, None).zeroize_witnesses)
+    }
+
+    /// Caches the witness like `cache_witness`, wiping the scalars of the
+    /// input of the frame afterwards if `zeroize_witnesses` is set, as read
+    /// from the Lurk config by the caller
+    pub(crate) fn cache_witness_with(
+        &mut self,
+        s: &Store<F>,
+        zeroize_witnesses: bool,
+    ) -> Result<(), SynthesisError> {
         let _ = self.cached_witness.get_or_try_init(|| {
-            let mut wcs = WitnessCS::new();
+            let mut wcs = ZeroizingWitnessCS::new();
 
-            let z_scalar = s.to_scalar_vector(self.input.as_ref().unwrap());
+            let mut z_scalar = s.to_scalar_vector(self.input.as_ref().unwrap());
 
-            let mut bogus_cs = WitnessCS::<F>::new();
+            let mut bogus_cs = ZeroizingWitnessCS::<F>::new();
             let z: Vec<AllocatedNum<F>> = z_scalar
                 .iter()
                 .map(|x| AllocatedNum::alloc_infallible(&mut bogus_cs, || *x))
                 .collect::<Vec<_>>();
 
             let output =
-                nova::traits::circuit::StepCircuit::synthesize(self, &mut wcs, z.as_slice())?;
-            Ok::<_, SynthesisError>((wcs, output))
+                nova::traits::circuit::StepCircuit::synthesize(self, &mut wcs, z.as_slice());
+            if zeroize_witnesses {
+                zeroize_scalars(&mut z_scalar);
+            }
+            Ok::<_, SynthesisError>((wcs, output?))
         })?;
         Ok(())
     }
 
+    /// Caches the witness like `cache_witness`, unless the witnesses accounted
+    /// for in `cached_bytes` already reached the `witness_cache_limit_bytes` of
+    /// `settings`. The size of the witness is added to `cached_bytes` when it
+    /// gets cached.
+    ///
+    /// Caching is also skipped while the host is under memory pressure, as told
+    /// by `proof::under_memory_pressure`.
+    ///
+    /// Frames that skip caching have their witnesses computed on demand during
+    /// folding. The limit is checked before caching, so concurrent callers can
+    /// exceed it by the size of a few witnesses.
+    pub fn cache_witness_within(
+        &mut self,
+        s: &Store<F>,
+        cached_bytes: &AtomicUsize,
+        settings: &ProvingSettings,
+    ) -> Result<(), SynthesisError> {
+        if settings
+            .witness_cache_limit_bytes
+            .is_some_and(|limit| cached_bytes.load(Ordering::Relaxed) >= limit)
+            || self.cached_witness.get().is_some()
+            || settings.under_memory_pressure()
+        {
+            return Ok(());
+        }
+        self.cache_witness_with(s, settings.zeroize_witnesses)?;
+        let witness_bytes = self.cached_witness_bytes().unwrap_or_default();
+        cached_bytes.fetch_add(witness_bytes, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Returns the size in bytes of the witness cached by `cache_witness`, if any
+    #[inline]
+    pub fn cached_witness_bytes(&self) -> Option<usize> {
+        self.cached_witness
+            .get()
+            .map(|(wcs, _)| std::mem::size_of_val(wcs.aux_slice()))
+    }
+
+    /// Returns the number of elements of the witness cached by `cache_witness`,
+    /// if any
+    #[inline]
+    pub fn cached_witness_len(&self) -> Option<usize> {
+        self.cached_witness
+            .get()
+            .map(|(wcs, _)| wcs.aux_slice().len())
+    }
+
+    /// Wipes and drops the witness cached by `cache_witness`, if any. Meant to
+    /// be called once the frame has been folded, when `zeroize_witnesses` is
+    /// set.
+    ///
+    /// The assignments of the witness are overwritten before being released to
+    /// the allocator. The values of the output variables are not, since
+    /// `AllocatedNum` doesn't expose them mutably, and neither are the copies
+    /// made by the folding scheme itself.
+    #[inline]
+    pub fn clear_cached_witness(&mut self) {
+        drop(self.take_wiped_witness());
+    }
+
+    /// Takes the witness cached by `cache_witness` out of `self`, once wiped
+    fn take_wiped_witness(&mut self) -> Option<(ZeroizingWitnessCS<F>, Vec<AllocatedNum<F>>)> {
+        let (mut wcs, output) = self.cached_witness.take()?;
+        wcs.wipe();
+        Some((wcs, output))
+    }
+
+    /// Whether `self` and `other` run the same circuit on the same frames, in
+    /// which case they have the same witness
+    pub fn is_identical_to(&self, other: &Self) -> bool {
+        let same_frames = match (&self.frames, &other.frames) {
+            (Some(frames), Some(other_frames)) => {
+                frames.len() == other_frames.len()
+                    && frames.iter().zip(other_frames).all(|(frame, other_frame)| {
+                        frame.pc == other_frame.pc
+                            && frame.blank == other_frame.blank
+                            && frame.input == other_frame.input
+                            && frame.output == other_frame.output
+                    })
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        self.pc == other.pc
+            && self.next_pc == other.next_pc
+            && self.num_frames == other.num_frames
+            && self.input == other.input
+            && self.output == other.output
+            && same_frames
+    }
+
+    /// Caches the witness of each step of `steps` that is identical to the step
+    /// before it, as told by `is_identical_to`, by copying the witness of the
+    /// first step of its run instead of synthesizing it again. Returns the
+    /// number of witnesses copied.
+    ///
+    /// This is all that runs of identical steps save: each step must still be
+    /// folded on its own. Folding a step combines it with the running instance,
+    /// which changes after every step, so the cross term, its commitment and
+    /// the folding challenge differ from one step of a run to the next, and
+    /// Nova has no sound way to fold several copies of an instance at once.
+    ///
+    /// Consecutive steps can only be identical if their state is a fixed point
+    /// of the step function, e.g. a terminal continuation, so such runs are
+    /// rare in traces built by `build_frames`. Witnesses are cached for whole
+    /// runs at once, which takes as much memory as caching them one by one.
+    pub fn share_identical_witnesses(
+        steps: &mut [Self],
+        s: &Store<F>,
+    ) -> Result<usize, SynthesisError> {
+        let mut copied = 0;
+        for i in 1..steps.len() {
+            let (done, rest) = steps.split_at_mut(i);
+            let (previous, step) = (&mut done[i - 1], &mut rest[0]);
+            if step.cached_witness.get().is_some() || !step.is_identical_to(previous) {
+                continue;
+            }
+            previous.cache_witness(s)?;
+            let (w, output) = previous.cached_witness.get().expect("witness just cached");
+            let mut wcs = ZeroizingWitnessCS::new();
+            wcs.extend_aux(w.aux_slice());
+            let _ = step.cached_witness.set((wcs, output.clone()));
+            copied += 1;
+        }
+        Ok(copied)
+    }
+
     #[inline]
     pub fn precedes(&self, maybe_next: &Self) -> bool {
         self.output == maybe_next.input
@@ -165,16 +343,8 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
 
     pub fn blank(folding_config: Arc<FoldingConfig<F, C>>, pc: usize) -> Self {
         let (lurk_step, cprocs, rc) = match &*folding_config {
-            FoldingConfig::IVC(lang, rc) => (
-                Arc::new(make_eval_step_from_config(&EvalConfig::new_ivc(lang))),
-                None,
-                *rc,
-            ),
-            FoldingConfig::NIVC(lang, rc) => (
-                Arc::new(make_eval_step_from_config(&EvalConfig::new_nivc(lang))),
-                Some(make_cprocs_funcs_from_lang(lang).into()),
-                *rc,
-            ),
+            FoldingConfig::IVC(lang, rc) => (ivc_step(lang), None, *rc),
+            FoldingConfig::NIVC(lang, rc) => (nivc_step(lang), Some(cproc_funcs(lang)), *rc),
         };
         let num_frames = if pc == 0 { rc } else { 1 };
         Self {
@@ -202,7 +372,7 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
             Vec::with_capacity((frames.len() + reduction_count - 1) / reduction_count);
         match folding_config.as_ref() {
             FoldingConfig::IVC(lang, _) => {
-                let lurk_step = Arc::new(make_eval_step_from_config(&EvalConfig::new_ivc(lang)));
+                let lurk_step = ivc_step(lang);
                 for chunk in frames.chunks(reduction_count) {
                     let output = chunk
                         .last()
@@ -243,8 +413,8 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
                 }
             }
             FoldingConfig::NIVC(lang, _) => {
-                let lurk_step = Arc::new(make_eval_step_from_config(&EvalConfig::new_nivc(lang)));
-                let cprocs: Arc<[Func]> = make_cprocs_funcs_from_lang(lang).into();
+                let lurk_step = nivc_step(lang);
+                let cprocs = cproc_funcs(lang);
                 let mut chunk_start_idx = 0;
                 while chunk_start_idx < frames.len() {
                     let first_frame = &frames[chunk_start_idx];
@@ -332,6 +502,25 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
         multi_frames
     }
 
+    /// The number of steps `from_frames` builds out of `frames` with the
+    /// reduction count `reduction_count`, without building them. Frames with a
+    /// program counter other than `0` are coprocessor calls, which NIVC folds in
+    /// a step of their own, so they also cut the runs of Lurk frames chunked into
+    /// steps. In IVC, every frame has the program counter `0`.
+    pub fn count_steps(frames: &[Frame], reduction_count: usize) -> usize {
+        let mut num_steps = 0;
+        let mut run_len = 0;
+        for frame in frames {
+            if frame.pc == 0 {
+                run_len += 1;
+            } else {
+                num_steps += (run_len + reduction_count - 1) / reduction_count + 1;
+                run_len = 0;
+            }
+        }
+        num_steps + (run_len + reduction_count - 1) / reduction_count
+    }
+
     pub fn build_frames(
         expr: Ptr,
         env: Ptr,
@@ -339,7 +528,30 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
         limit: usize,
         ec: &EvalConfig<'_, F, C>,
     ) -> Result<Vec<Frame>, ProofError> {
-        let cont = store.cont_outermost();
+        Self::build_frames_with_cont(expr, env, store.cont_outermost(), store, limit, ec)
+    }
+
+    /// Like `build_frames`, but starting from the continuation `cont` instead of
+    /// the outermost one, so the initial state `[expr, env, cont]` is entirely
+    /// chosen by the caller.
+    ///
+    /// Errors with `ProofError::InvalidInitialEnv` if `env` isn't an environment
+    /// fully interned in `store` and with `ProofError::InvalidInitialCont` if
+    /// `cont` isn't a continuation.
+    pub fn build_frames_with_cont(
+        expr: Ptr,
+        env: Ptr,
+        cont: Ptr,
+        store: &Store<F>,
+        limit: usize,
+        ec: &EvalConfig<'_, F, C>,
+    ) -> Result<Vec<Frame>, ProofError> {
+        if store.fetch_env(&env).is_none() {
+            return Err(ProofError::InvalidInitialEnv);
+        }
+        if !matches!(cont.tag(), Tag::Cont(_)) {
+            return Err(ProofError::InvalidInitialCont);
+        }
         let lurk_step = make_eval_step_from_config(ec);
         let lang = ec.lang();
         let cprocs = make_cprocs_funcs_from_lang(lang);
@@ -371,6 +583,44 @@ impl<'a, F: LurkField, C: Coprocessor<F>> MultiFrame<'a, F, C> {
     pub fn program_counter(&self) -> usize {
         self.pc
     }
+
+    /// Checks that the program counter each step claims, which selects the
+    /// circuit folding it, agrees with what the step's circuit is synthesized
+    /// from: its frames must have been computed by the same function, and the
+    /// `next_pc` output by the circuit of the previous step must select it.
+    ///
+    /// Errors with `ProofError::PcInconsistency` at the first step that
+    /// disagrees. Folding such a step would produce an invalid proof.
+    pub fn check_pc_consistency(steps: &[Self]) -> Result<(), ProofError> {
+        let mut prev_next_pc = None;
+        for (step_index, step) in steps.iter().enumerate() {
+            prev_next_pc = Some(step.check_pc_after(step_index, prev_next_pc)?);
+        }
+        Ok(())
+    }
+
+    /// The check of `check_pc_consistency` for `self`, the step at
+    /// `step_index`, given the `next_pc` of the previous step, if any. Returns
+    /// the `next_pc` of `self`, to check the next step with.
+    pub(crate) fn check_pc_after(
+        &self,
+        step_index: usize,
+        prev_next_pc: Option<usize>,
+    ) -> Result<usize, ProofError> {
+        let frames_agree = match self.folding_config.as_ref() {
+            // IVC has a single circuit, whatever the frames compute
+            FoldingConfig::IVC(..) => self.pc == 0,
+            FoldingConfig::NIVC(..) => self
+                .frames
+                .iter()
+                .flatten()
+                .all(|frame| frame.pc == self.pc),
+        };
+        if !frames_agree || prev_next_pc.is_some_and(|next_pc| next_pc != self.pc) {
+            return Err(ProofError::PcInconsistency { step_index });
+        }
+        Ok(self.next_pc)
+    }
 }
 
 impl CEKState<Ptr> for Vec<Ptr> {
@@ -604,7 +854,7 @@ fn synthesize_frames_parallel<F: LurkField, CS: ConstraintSystem<F>, C: Coproces
         .par_iter()
         .enumerate()
         .map(|(i, frame)| {
-            let mut frame_cs = WitnessCS::new();
+            let mut frame_cs = ZeroizingWitnessCS::new();
             // The first frame will take as input the actual input of the circuit.
             // Subsequent frames would have to take the output of the previous one as input.
             // But since we know the values of each frame input and we are generating the
@@ -912,6 +1162,9 @@ where
         }
     }
 
+    /// The secondary circuit is trivial and the same for all steps, so the
+    /// provers build it once per proof instead of once per step, as long as
+    /// `supernova::secondary_circuit_is_invariant` holds.
     fn secondary_circuit(&self) -> C2<F> {
         Default::default()
     }
@@ -920,16 +1173,19 @@ where
 #[cfg(test)]
 mod tests {
     use bellpepper_core::test_cs::TestConstraintSystem;
+    use ff::Field;
     use halo2curves::bn256::Fr as Bn;
     use halo2curves::grumpkin::Fr as Gr;
     use pasta_curves::{Fp, Fq};
 
     use crate::{
+        coprocessor::test::DumbCoprocessor,
         eval::lang::Coproc,
         lem::{
             circuit::AllocatedVal,
             eval::{eval_step, evaluate},
         },
+        state::user_sym,
     };
 
     use super::*;
@@ -1100,4 +1356,250 @@ mod tests {
 
         assert!(!cs.is_satisfied());
     }
+
+    #[test]
+    fn test_cache_witness_within() {
+        let store = Store::<Fq>::default();
+        let expr = store.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+        let frames = evaluate::<Fq, Coproc<Fq>>(None, expr, &store, 10).unwrap();
+        let lang = Arc::new(Lang::<Fq, Coproc<Fq>>::new());
+        let folding_config = Arc::new(FoldingConfig::new_ivc(lang, 1));
+        let mut steps = MultiFrame::from_frames(&frames, &store, &folding_config);
+        assert!(steps.len() > 2);
+
+        let cached_bytes = AtomicUsize::new(0);
+        let limited = ProvingSettings {
+            witness_cache_limit_bytes: Some(1),
+            ..Default::default()
+        };
+        steps[0]
+            .cache_witness_within(&store, &cached_bytes, &limited)
+            .unwrap();
+        let witness_bytes = steps[0].cached_witness_bytes().unwrap();
+        assert_eq!(cached_bytes.load(Ordering::Relaxed), witness_bytes);
+
+        // the limit has been reached, so the next witness isn't cached
+        steps[1]
+            .cache_witness_within(&store, &cached_bytes, &limited)
+            .unwrap();
+        assert!(steps[1].cached_witness_bytes().is_none());
+        assert_eq!(cached_bytes.load(Ordering::Relaxed), witness_bytes);
+
+        // but it is without a limit
+        steps[1]
+            .cache_witness_within(&store, &cached_bytes, &ProvingSettings::default())
+            .unwrap();
+        assert!(steps[1].cached_witness_bytes().is_some());
+    }
+
+    #[test]
+    fn test_clear_cached_witness() {
+        let store = Store::<Fq>::default();
+        let expr = store.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+        let frames = evaluate::<Fq, Coproc<Fq>>(None, expr, &store, 10).unwrap();
+        let lang = Arc::new(Lang::<Fq, Coproc<Fq>>::new());
+        let folding_config = Arc::new(FoldingConfig::new_ivc(lang, 1));
+        let mut steps = MultiFrame::from_frames(&frames, &store, &folding_config);
+
+        steps[0].cache_witness(&store).unwrap();
+        let witness_len = steps[0].cached_witness_len().unwrap();
+        assert!(steps[0]
+            .cached_witness
+            .get()
+            .unwrap()
+            .0
+            .aux_slice()
+            .iter()
+            .any(|x| *x != Fq::ZERO));
+
+        // the buffer of the witness is overwritten before being released
+        let (wcs, _) = steps[0].take_wiped_witness().unwrap();
+        assert_eq!(wcs.aux_slice().len(), witness_len);
+        assert!(wcs.aux_slice().iter().all(|x| *x == Fq::ZERO));
+        assert!(steps[0].cached_witness_len().is_none());
+    }
+
+    #[test]
+    fn test_check_pc_consistency() {
+        let store = Store::<Fq>::default();
+        let mut lang = Lang::<Fq, DumbCoprocessor<Fq>>::new();
+        lang.add_coprocessor(user_sym("cproc-dumb"), DumbCoprocessor::new());
+        let lang = Arc::new(lang);
+        let expr = store
+            .read_with_default_state("(+ 1 (cproc-dumb 2 3))")
+            .unwrap();
+        let frames = MultiFrame::build_frames(
+            expr,
+            store.intern_empty_env(),
+            &store,
+            100,
+            &EvalConfig::new_nivc(&lang),
+        )
+        .unwrap();
+        let folding_config = Arc::new(FoldingConfig::new_nivc(lang, 1));
+        let steps = MultiFrame::from_frames(&frames, &store, &folding_config);
+        let cproc_index = steps.iter().position(|step| step.pc == 1).unwrap();
+        assert!(MultiFrame::check_pc_consistency(&steps).is_ok());
+        assert_eq!(MultiFrame::count_steps(&frames, 1), steps.len());
+
+        // the coprocessor step claims to be a Lurk step
+        let mut tampered = steps.clone();
+        tampered[cproc_index].pc = 0;
+        assert!(matches!(
+            MultiFrame::check_pc_consistency(&tampered),
+            Err(ProofError::PcInconsistency { step_index }) if step_index == cproc_index
+        ));
+
+        // the step before the coprocessor step doesn't select it
+        let mut tampered = steps;
+        tampered[cproc_index - 1].next_pc = 0;
+        assert!(matches!(
+            MultiFrame::check_pc_consistency(&tampered),
+            Err(ProofError::PcInconsistency { step_index }) if step_index == cproc_index
+        ));
+    }
+
+    #[test]
+    fn test_share_identical_witnesses() {
+        let store = Store::<Fq>::default();
+        let lang = Arc::new(Lang::<Fq, Coproc<Fq>>::new());
+        let ec = EvalConfig::new_ivc(&lang);
+        let expr = store.read_with_default_state("(+ 1 2)").unwrap();
+        let frames =
+            MultiFrame::build_frames(expr, store.intern_empty_env(), &store, 10, &ec).unwrap();
+        let folding_config = Arc::new(FoldingConfig::new_ivc(lang, 1));
+        let last = frames.last().unwrap().clone();
+        let repeated = [frames, vec![last]].concat();
+        store.hydrate_z_cache();
+
+        let mut steps = MultiFrame::from_frames(&repeated, &store, &folding_config);
+        let n = steps.len();
+        assert!(!steps[n - 2].is_identical_to(&steps[n - 3]));
+        assert!(steps[n - 1].is_identical_to(&steps[n - 2]));
+        assert_eq!(
+            MultiFrame::share_identical_witnesses(&mut steps, &store).unwrap(),
+            1
+        );
+        assert!(steps[..n - 2]
+            .iter()
+            .all(|step| step.cached_witness_len().is_none()));
+
+        // the copied witness is the one the step would have computed
+        let mut expected = MultiFrame::from_frames(&repeated, &store, &folding_config);
+        expected[n - 1].cache_witness(&store).unwrap();
+        let witness = |step: &MultiFrame<'_, Fq, Coproc<Fq>>| {
+            let (w, output) = step.cached_witness.get().unwrap();
+            let output = output.iter().map(|x| x.get_value()).collect::<Vec<_>>();
+            (w.aux_slice().to_vec(), output)
+        };
+        assert_eq!(witness(&steps[n - 1]), witness(&expected[n - 1]));
+    }
+
+    #[test]
+    fn test_blank_funcs_cached_on_lang() {
+        let mut lang = Lang::<Fq, DumbCoprocessor<Fq>>::new();
+        lang.add_coprocessor(user_sym("cproc-dumb"), DumbCoprocessor::new());
+        let lang = Arc::new(lang);
+
+        // folding configs sharing a `Lang` share its functions, whatever their
+        // reduction counts
+        let ivc = |rc| MultiFrame::blank(Arc::new(FoldingConfig::new_ivc(lang.clone(), rc)), 0);
+        assert!(Arc::ptr_eq(&ivc(1).lurk_step, &ivc(10).lurk_step));
+        let nivc = |pc| MultiFrame::blank(Arc::new(FoldingConfig::new_nivc(lang.clone(), 10)), pc);
+        let (blank0, blank1) = (nivc(0), nivc(1));
+        assert!(Arc::ptr_eq(&blank0.lurk_step, &blank1.lurk_step));
+        assert!(Arc::ptr_eq(
+            blank0.cprocs.as_ref().unwrap(),
+            blank1.cprocs.as_ref().unwrap()
+        ));
+        assert!(!Arc::ptr_eq(&blank0.lurk_step, &ivc(10).lurk_step));
+
+        // adding a coprocessor rebuilds them
+        let mut extended = (*lang).clone();
+        extended.add_coprocessor(user_sym("cproc-dumb-2"), DumbCoprocessor::new());
+        let extended = MultiFrame::blank(Arc::new(FoldingConfig::new_nivc(extended.into(), 10)), 0);
+        assert_eq!(extended.cprocs.as_ref().unwrap().len(), 2);
+        assert_ne!(extended.lurk_step, blank0.lurk_step);
+    }
+
+    #[test]
+    fn test_count_steps() {
+        let store = Store::<Fq>::default();
+        let mut lang = Lang::<Fq, DumbCoprocessor<Fq>>::new();
+        lang.add_coprocessor(user_sym("cproc-dumb"), DumbCoprocessor::new());
+        let lang = Arc::new(lang);
+        let expr = store
+            .read_with_default_state("(+ (cproc-dumb 1 2) (+ 3 (cproc-dumb 4 5)))")
+            .unwrap();
+        for nivc in [false, true] {
+            let ec = if nivc {
+                EvalConfig::new_nivc(&lang)
+            } else {
+                EvalConfig::new_ivc(&lang)
+            };
+            let frames =
+                MultiFrame::build_frames(expr, store.intern_empty_env(), &store, 100, &ec).unwrap();
+            for rc in [1, 2, 3, 10] {
+                let folding_config = Arc::new(if nivc {
+                    FoldingConfig::new_nivc(lang.clone(), rc)
+                } else {
+                    FoldingConfig::new_ivc(lang.clone(), rc)
+                });
+                let steps = MultiFrame::from_frames(&frames, &store, &folding_config);
+                assert_eq!(MultiFrame::count_steps(&frames, rc), steps.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_frames_with_cont() {
+        let store = Store::<Fq>::default();
+        let lang = Lang::<Fq, Coproc<Fq>>::new();
+        let ec = EvalConfig::new_ivc(&lang);
+        let expr = store.read_with_default_state("(+ x 1)").unwrap();
+        let env = store.push_binding(
+            store.intern_user_symbol("x"),
+            store.num_u64(2),
+            store.intern_empty_env(),
+        );
+
+        // the expression can use the bindings of the initial environment
+        let frames =
+            MultiFrame::<'_, Fq, Coproc<Fq>>::build_frames(expr, env, &store, 10, &ec).unwrap();
+        let output = &frames.last().unwrap().output;
+        assert_eq!(output[0], store.num_u64(3));
+        assert_eq!(output[2], store.cont_terminal());
+
+        // which are unbound otherwise
+        let empty_env = store.intern_empty_env();
+        let frames =
+            MultiFrame::<'_, Fq, Coproc<Fq>>::build_frames(expr, empty_env, &store, 10, &ec)
+                .unwrap();
+        assert_eq!(frames.last().unwrap().output[2], store.cont_error());
+
+        // starting from the terminal continuation, there's nothing to do
+        let frames = MultiFrame::<'_, Fq, Coproc<Fq>>::build_frames_with_cont(
+            expr,
+            env,
+            store.cont_terminal(),
+            &store,
+            10,
+            &ec,
+        )
+        .unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].output[2], store.cont_terminal());
+
+        let not_env = store.num_u64(2);
+        assert!(matches!(
+            MultiFrame::<'_, Fq, Coproc<Fq>>::build_frames(expr, not_env, &store, 10, &ec),
+            Err(ProofError::InvalidInitialEnv)
+        ));
+        assert!(matches!(
+            MultiFrame::<'_, Fq, Coproc<Fq>>::build_frames_with_cont(
+                expr, env, not_env, &store, 10, &ec
+            ),
+            Err(ProofError::InvalidInitialCont)
+        ));
+    }
 }
@@ -38,6 +38,15 @@ use super::pointers::{Ptr, RawPtr, ZPtr};
 ///
 /// The `Store` also provides an infra to speed up interning strings and symbols.
 /// This data is saved in `string_ptr_cache` and `symbol_ptr_cache`.
+///
+/// # Concurrent mutation
+/// The `Store` is append-only: all of its methods take `&self` and only ever
+/// add entries to its frozen collections, never change or remove them. So the
+/// data and the hash of a `Ptr` never change once it's interned, and frames
+/// generated from a store stay valid while other threads keep interning into
+/// it, including while the frames are being proven. That's why there is no
+/// snapshot of a `Store` to prove against: it would guarantee nothing more and
+/// copying the store would cost time and memory linear in its size.
 #[derive(Debug)]
 pub struct Store<F: LurkField> {
     f_elts: FrozenIndexSet<Box<FWrap<F>>>,
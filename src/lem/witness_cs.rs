@@ -0,0 +1,230 @@
+//! A witness generator whose buffers are wiped once they are no longer needed.
+//!
+//! The `WitnessCS` of `bellpepper` doesn't expose its assignments mutably, so
+//! the witnesses cached by `MultiFrame::cache_witness` used to be released to
+//! the allocator as they were. `ZeroizingWitnessCS` works like `WitnessCS` but
+//! overwrites its assignments when it's wiped or dropped, as well as the old
+//! buffers it leaves behind when it grows.
+
+use bellpepper_core::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use ff::PrimeField;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Overwrites `scalars` with zeros through volatile writes, so the compiler
+/// can't elide them as dead stores
+pub(crate) fn zeroize_scalars<F: PrimeField>(scalars: &mut [F]) {
+    for x in scalars.iter_mut() {
+        // SAFETY: `x` is a valid, aligned and exclusive reference
+        unsafe { std::ptr::write_volatile(x, F::ZERO) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Overwrites the elements of `v` with zeros, and the bytes of its spare
+/// capacity, which may hold elements that have been truncated away
+fn wipe_vec<F: PrimeField>(v: &mut Vec<F>) {
+    zeroize_scalars(v);
+    v.spare_capacity_mut().zeroize();
+}
+
+/// Makes room for `additional` more elements in `v`. Unlike `Vec::reserve`,
+/// the buffer the elements are moved out of is wiped before being released
+fn reserve_wiping<F: PrimeField>(v: &mut Vec<F>, additional: usize) {
+    if v.capacity() - v.len() >= additional {
+        return;
+    }
+    let capacity = (v.len() + additional).max(2 * v.capacity());
+    let mut grown = Vec::with_capacity(capacity);
+    grown.extend_from_slice(v);
+    wipe_vec(v);
+    *v = grown;
+}
+
+#[inline]
+fn push_wiping<F: PrimeField>(v: &mut Vec<F>, x: F) -> usize {
+    reserve_wiping(v, 1);
+    v.push(x);
+    v.len() - 1
+}
+
+#[inline]
+fn extend_wiping<F: PrimeField>(v: &mut Vec<F>, xs: &[F]) {
+    reserve_wiping(v, xs.len());
+    v.extend_from_slice(xs);
+}
+
+/// Appends `n` zeros to `v`, returning them
+#[inline]
+fn allocate_wiping<F: PrimeField>(v: &mut Vec<F>, n: usize) -> &mut [F] {
+    let start = v.len();
+    reserve_wiping(v, n);
+    v.resize(start + n, F::ZERO);
+    &mut v[start..]
+}
+
+/// A witness generator like `bellpepper`'s `WitnessCS`, whose assignments are
+/// wiped when it's dropped
+#[derive(Clone, Debug)]
+pub(crate) struct ZeroizingWitnessCS<F: PrimeField> {
+    input_assignment: Vec<F>,
+    aux_assignment: Vec<F>,
+}
+
+impl<F: PrimeField> ZeroizingWitnessCS<F> {
+    /// Overwrites the assignments with zeros in place. Their lengths are kept,
+    /// so they can be read back
+    pub(crate) fn wipe(&mut self) {
+        wipe_vec(&mut self.input_assignment);
+        wipe_vec(&mut self.aux_assignment);
+    }
+}
+
+impl<F: PrimeField> Zeroize for ZeroizingWitnessCS<F> {
+    fn zeroize(&mut self) {
+        self.wipe();
+        self.input_assignment.clear();
+        self.aux_assignment.clear();
+    }
+}
+
+impl<F: PrimeField> Drop for ZeroizingWitnessCS<F> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<F: PrimeField> ZeroizeOnDrop for ZeroizingWitnessCS<F> {}
+
+impl<F: PrimeField> ConstraintSystem<F> for ZeroizingWitnessCS<F> {
+    type Root = Self;
+
+    fn new() -> Self {
+        Self {
+            input_assignment: vec![F::ONE],
+            aux_assignment: vec![],
+        }
+    }
+
+    fn alloc<G, A, AR>(&mut self, _: A, f: G) -> Result<Variable, SynthesisError>
+    where
+        G: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let i = push_wiping(&mut self.aux_assignment, f()?);
+        Ok(Variable::new_unchecked(Index::Aux(i)))
+    }
+
+    fn alloc_input<G, A, AR>(&mut self, _: A, f: G) -> Result<Variable, SynthesisError>
+    where
+        G: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let i = push_wiping(&mut self.input_assignment, f()?);
+        Ok(Variable::new_unchecked(Index::Input(i)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, _a: LA, _b: LB, _c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        // a witness generator doesn't keep track of constraints
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn is_extensible() -> bool {
+        true
+    }
+
+    fn extend(&mut self, other: &Self) {
+        extend_wiping(&mut self.input_assignment, &other.input_assignment[1..]);
+        extend_wiping(&mut self.aux_assignment, &other.aux_assignment);
+    }
+
+    fn is_witness_generator(&self) -> bool {
+        true
+    }
+
+    fn extend_inputs(&mut self, new_inputs: &[F]) {
+        extend_wiping(&mut self.input_assignment, new_inputs);
+    }
+
+    fn extend_aux(&mut self, new_aux: &[F]) {
+        extend_wiping(&mut self.aux_assignment, new_aux);
+    }
+
+    fn allocate_empty(&mut self, aux_n: usize, inputs_n: usize) -> (&mut [F], &mut [F]) {
+        (
+            allocate_wiping(&mut self.aux_assignment, aux_n),
+            allocate_wiping(&mut self.input_assignment, inputs_n),
+        )
+    }
+
+    fn allocate_empty_inputs(&mut self, n: usize) -> &mut [F] {
+        allocate_wiping(&mut self.input_assignment, n)
+    }
+
+    fn allocate_empty_aux(&mut self, n: usize) -> &mut [F] {
+        allocate_wiping(&mut self.aux_assignment, n)
+    }
+
+    fn inputs_slice(&self) -> &[F] {
+        &self.input_assignment
+    }
+
+    fn aux_slice(&self) -> &[F] {
+        &self.aux_assignment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper_core::num::AllocatedNum;
+    use ff::Field;
+    use pasta_curves::Fq;
+
+    use super::*;
+
+    #[test]
+    fn test_wipe() {
+        let mut cs = ZeroizingWitnessCS::<Fq>::new();
+        for i in 1..=100 {
+            AllocatedNum::alloc_infallible(&mut cs, || Fq::from(i));
+        }
+        cs.extend_aux(&[Fq::from(101), Fq::from(102)]);
+        assert_eq!(cs.aux_slice().len(), 102);
+        assert!(cs.aux_slice().iter().all(|x| !bool::from(x.is_zero())));
+
+        // the elements truncated away linger in the spare capacity, which is
+        // wiped as well
+        cs.aux_assignment.truncate(50);
+        cs.wipe();
+        assert_eq!(cs.aux_slice().len(), 50);
+        assert!(cs.aux_slice().iter().all(|x| *x == Fq::ZERO));
+        // SAFETY: these elements have been initialized before being truncated
+        let truncated =
+            unsafe { std::slice::from_raw_parts(cs.aux_assignment.as_ptr().add(50), 52) };
+        assert!(truncated.iter().all(|x| *x == Fq::ZERO));
+        assert_eq!(cs.inputs_slice(), &[Fq::ZERO]);
+
+        cs.zeroize();
+        assert!(cs.aux_slice().is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::{coprocessor::Coprocessor, error::ProofError};
+
+use super::{nova::CurveCycleEquipped, RecursiveSNARKTrait};
+
+/// The distribution of the latencies measured by [`bench_verify`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    /// Number of measured verifications
+    pub iterations: usize,
+    /// Fastest verification
+    pub min: Duration,
+    /// Median latency
+    pub p50: Duration,
+    /// 90th percentile of the latency
+    pub p90: Duration,
+    /// 99th percentile of the latency
+    pub p99: Duration,
+    /// Slowest verification
+    pub max: Duration,
+    /// Mean latency
+    pub mean: Duration,
+}
+
+impl LatencyStats {
+    /// Computes the distribution of `durations`, returning `None` if it's empty.
+    /// Percentiles use the nearest-rank method, so they're always one of the
+    /// measured durations.
+    pub fn from_durations(mut durations: Vec<Duration>) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort_unstable();
+        let n = durations.len();
+        let percentile = |p: usize| durations[((p * n + 99) / 100).max(1) - 1];
+        let total: Duration = durations.iter().sum();
+        Some(Self {
+            iterations: n,
+            min: durations[0],
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: durations[n - 1],
+            mean: total / n as u32,
+        })
+    }
+}
+
+/// Verifies `proof` `iterations` times, returning the distribution of the
+/// verification latency. The first verification isn't measured, so that the
+/// caches it fills don't skew the results.
+///
+/// Errors with `ProofError::VerificationFailed` if the proof doesn't verify,
+/// since measuring a rejection says nothing about the latency of accepted
+/// proofs, and with `ProofError::NoSteps` if `iterations` is zero.
+pub fn bench_verify<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a, P>(
+    pp: &P::PublicParams,
+    proof: &P,
+    z0: &[F],
+    zi: &[F],
+    iterations: usize,
+) -> Result<LatencyStats, ProofError>
+where
+    P: RecursiveSNARKTrait<'a, F, C>,
+{
+    if iterations == 0 {
+        return Err(ProofError::NoSteps);
+    }
+    if !proof.verify(pp, z0, zi)? {
+        return Err(ProofError::VerificationFailed);
+    }
+    let mut durations = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let verified = proof.verify(pp, z0, zi)?;
+        durations.push(start.elapsed());
+        if !verified {
+            return Err(ProofError::VerificationFailed);
+        }
+    }
+    Ok(LatencyStats::from_durations(durations).expect("iterations isn't zero"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats() {
+        assert_eq!(LatencyStats::from_durations(vec![]), None);
+
+        let durations = (1..=100).rev().map(Duration::from_millis).collect();
+        let stats = LatencyStats::from_durations(durations).unwrap();
+        assert_eq!(stats.iterations, 100);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.p50, Duration::from_millis(50));
+        assert_eq!(stats.p90, Duration::from_millis(90));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+
+        let stats = LatencyStats::from_durations(vec![Duration::from_millis(7)]).unwrap();
+        assert_eq!(stats.p50, Duration::from_millis(7));
+        assert_eq!(stats.p99, Duration::from_millis(7));
+    }
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{coprocessor::Coprocessor, error::ProofError, field::LurkField, hash::PoseidonCache};
+
+use super::{nova::CurveCycleEquipped, RecursiveSNARKTrait};
+
+/// Domain separators of the hashes of the tree, so that leaves, inner nodes and
+/// the root can't be mistaken for one another
+const LEAF: u64 = 0;
+const NODE: u64 = 1;
+const ROOT: u64 = 2;
+
+/// The path from a leaf of a Merkle tree built by [`commit_digests`] to its
+/// root, proving that the digest of the leaf is part of the committed batch, at
+/// the position given by `index`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct InclusionPath<F: LurkField> {
+    /// Position of the leaf in the batch
+    pub index: usize,
+    /// Number of leaves of the tree, which the root commits to
+    pub num_leaves: usize,
+    /// The siblings of the nodes from the leaf up, each along with whether it's
+    /// the left child. Nodes left alone at the end of a level are promoted to
+    /// the next one without being hashed, so they have no sibling.
+    pub siblings: Vec<(F, bool)>,
+}
+
+impl<F: LurkField> InclusionPath<F> {
+    /// The root of the tree the path leads to from a leaf holding `digest`
+    pub fn root(&self, digest: F) -> F {
+        let poseidon_cache = PoseidonCache::<F>::default();
+        let leaf = hash_leaf(&poseidon_cache, digest);
+        let node = self.siblings.iter().fold(leaf, |node, (sibling, is_left)| {
+            if *is_left {
+                hash_node(&poseidon_cache, *sibling, node)
+            } else {
+                hash_node(&poseidon_cache, node, *sibling)
+            }
+        });
+        hash_root(&poseidon_cache, node, self.num_leaves)
+    }
+
+    /// Whether the path proves that `digest` is at position `self.index` of the
+    /// batch committed to by `root`. Since the root commits to the number of
+    /// leaves, the shape of the tree is known, and so which side each sibling
+    /// of the path must be on to lead to that position.
+    pub fn verify(&self, root: F, digest: F) -> bool {
+        self.sides_match_index() && self.root(digest) == root
+    }
+
+    /// Whether the siblings are on the sides, and at the levels, given by the
+    /// position of the leaf in a tree of `self.num_leaves` leaves
+    fn sides_match_index(&self) -> bool {
+        if self.index >= self.num_leaves {
+            return false;
+        }
+        let mut siblings = self.siblings.iter();
+        let (mut index, mut level_len) = (self.index, self.num_leaves);
+        while level_len > 1 {
+            // the last node of a level of odd length is promoted without being
+            // hashed
+            if index % 2 == 1 || index + 1 < level_len {
+                match siblings.next() {
+                    Some((_, is_left)) if *is_left == (index % 2 == 1) => (),
+                    _ => return false,
+                }
+            }
+            index /= 2;
+            level_len = (level_len + 1) / 2;
+        }
+        siblings.next().is_none()
+    }
+}
+
+fn hash_leaf<F: LurkField>(poseidon_cache: &PoseidonCache<F>, digest: F) -> F {
+    poseidon_cache.hash3(&[F::from_u64(LEAF), digest, F::ZERO])
+}
+
+fn hash_node<F: LurkField>(poseidon_cache: &PoseidonCache<F>, left: F, right: F) -> F {
+    poseidon_cache.hash3(&[F::from_u64(NODE), left, right])
+}
+
+fn hash_root<F: LurkField>(poseidon_cache: &PoseidonCache<F>, node: F, num_leaves: usize) -> F {
+    poseidon_cache.hash3(&[F::from_u64(ROOT), node, F::from_u64(num_leaves as u64)])
+}
+
+/// Builds a Merkle tree over `digests` with Poseidon and returns its root along
+/// with the inclusion path of each digest, in order.
+///
+/// The root also commits to the number of digests, so batches of different
+/// sizes can't share a root, and the root of an empty batch is the hash of
+/// zero leaves. Nodes left alone at the end of a level are promoted rather
+/// than duplicated, so that `[a, b, c]` and `[a, b, c, c]` don't collide
+/// either.
+pub fn commit_digests<F: LurkField>(digests: &[F]) -> (F, Vec<InclusionPath<F>>) {
+    let poseidon_cache = PoseidonCache::<F>::default();
+    let num_leaves = digests.len();
+    let mut paths = (0..num_leaves)
+        .map(|index| InclusionPath {
+            index,
+            num_leaves,
+            siblings: vec![],
+        })
+        .collect::<Vec<_>>();
+    // the leaves below each node of the current level
+    let mut leaves = (0..num_leaves).map(|i| i..i + 1).collect::<Vec<_>>();
+    let mut level = digests
+        .iter()
+        .map(|digest| hash_leaf(&poseidon_cache, *digest))
+        .collect::<Vec<_>>();
+    while level.len() > 1 {
+        let mut next_level = Vec::with_capacity((level.len() + 1) / 2);
+        let mut next_leaves = Vec::with_capacity((level.len() + 1) / 2);
+        for (nodes, ranges) in level.chunks(2).zip(leaves.chunks(2)) {
+            match (nodes, ranges) {
+                ([left, right], [left_leaves, right_leaves]) => {
+                    for path in &mut paths[left_leaves.clone()] {
+                        path.siblings.push((*right, false));
+                    }
+                    for path in &mut paths[right_leaves.clone()] {
+                        path.siblings.push((*left, true));
+                    }
+                    next_level.push(hash_node(&poseidon_cache, *left, *right));
+                    next_leaves.push(left_leaves.start..right_leaves.end);
+                }
+                _ => {
+                    next_level.push(nodes[0]);
+                    next_leaves.push(ranges[0].clone());
+                }
+            }
+        }
+        level = next_level;
+        leaves = next_leaves;
+    }
+    let top = level.first().copied().unwrap_or(F::ZERO);
+    (hash_root(&poseidon_cache, top, num_leaves), paths)
+}
+
+/// Builds a Merkle tree over the digests of `proofs`, as given by
+/// `RecursiveSNARKTrait::digest`, e.g. to anchor a batch of proofs on a ledger
+/// at once. See [`commit_digests`].
+pub fn commit_proofs<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a, P>(
+    proofs: &[P],
+) -> Result<(F, Vec<InclusionPath<F>>), ProofError>
+where
+    P: RecursiveSNARKTrait<'a, F, C> + Serialize,
+{
+    let digests = proofs
+        .iter()
+        .map(|proof| proof.digest())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(commit_digests(&digests))
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use pasta_curves::Fq;
+
+    use super::*;
+
+    #[test]
+    fn test_commit_digests() {
+        let (empty_root, paths) = commit_digests::<Fq>(&[]);
+        assert!(paths.is_empty());
+
+        for n in 1..=7 {
+            let digests = (0..n).map(|i| Fq::from(i as u64 + 10)).collect::<Vec<_>>();
+            let (root, paths) = commit_digests(&digests);
+            assert_ne!(root, empty_root);
+            assert_eq!(paths.len(), n);
+            for (i, (digest, path)) in digests.iter().zip(&paths).enumerate() {
+                assert_eq!(path.index, i);
+                assert!(path.verify(root, *digest));
+                assert!(!path.verify(root, *digest + Fq::ONE));
+                // the path of another position doesn't prove this one
+                let moved = InclusionPath {
+                    index: (i + 1) % n,
+                    ..path.clone()
+                };
+                assert_eq!(moved.verify(root, *digest), n == 1);
+            }
+        }
+
+        // promoting instead of duplicating the last node tells these apart
+        let digests = [Fq::from(1), Fq::from(2), Fq::from(3)];
+        let (root, _) = commit_digests(&digests);
+        let (padded_root, _) = commit_digests(&[digests[0], digests[1], digests[2], digests[2]]);
+        assert_ne!(root, padded_root);
+    }
+}
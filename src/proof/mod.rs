@@ -5,6 +5,52 @@
 //! has two instantiations:
 //! - the Nova proving system, implemented in the `nova` module.
 //! - the SuperNova proving system, implemented in the `supernova` module.
+//!
+//! ## Thread safety
+//!
+//! Since coprocessors are required to be `Send + Sync`, so are the provers
+//! (`NovaProver` and `SuperNovaProver`), the proofs and the public parameters
+//! of both proving systems. Verification only needs shared references to the
+//! public parameters, so a service can serve concurrent jobs from one instance
+//! of the parameters instead of cloning them per job, which is very expensive.
+//! The Nova parameters are already handed out behind an `Arc` by the
+//! `public_parameters` module and `supernova::SharedPublicParams` provides the
+//! same for SuperNova.
+//!
+//! ## Determinism
+//!
+//! Proving is deterministic: Nova and SuperNova don't blind their commitments
+//! and derive every challenge from a transcript of the prover's messages, so
+//! proving the same steps with the same public parameters produces the same
+//! proof, byte for byte. The parallel witness caching doesn't affect the result
+//! since steps are still folded in order. This lets an auditor reproduce a
+//! proof with `RecursiveSNARKTrait::reproduce_and_compare`.
+//!
+//! ## Running in memory
+//!
+//! Proving and verifying don't need a filesystem when the public parameters
+//! are passed as values, e.g. as set up by `nova::public_params` or
+//! deserialized from bytes, rather than loaded through the `public_parameters`
+//! disk cache. The remaining implicit accesses come from initialization:
+//! - the Lurk config is loaded on first use from the environment and a config
+//!   file, so `config::LURK_CONFIG` must be set beforehand, e.g. to
+//!   `config::Settings::in_memory()`;
+//! - the global rayon pool reads the CPU quota of the process from cgroup files
+//!   to size itself, unless it's built beforehand with an explicit number of
+//!   threads;
+//! - `min_available_memory_bytes` samples `/proc/meminfo`, so it must stay unset.
+//!
+//! The `lurk-in-memory-test` integration test checks that no file is read or
+//! written under these conditions.
+//!
+//! ## Tracing
+//!
+//! Proving logs under the `nova::prove_recursively` and
+//! `supernova::prove_recursively` spans (and their lazy counterparts), which are
+//! entered as children of the caller's current span. A service running several
+//! jobs can thus tell their logs apart by proving each job in a span of its own,
+//! e.g. `tracing::info_span!("job", id = %job_id).in_scope(|| prover.prove(..))`.
+//! The thread caching witnesses in parallel with folding enters the same span.
 
 /// An adapter to a Nova proving system implementation.
 pub mod nova;
@@ -12,24 +58,56 @@ pub mod nova;
 /// An adapter to a SuperNova proving system implementation.
 pub mod supernova;
 
+/// Merkle commitments to batches of proofs, to anchor them all at once.
+pub mod merkle;
+
+/// Measurement of the verification latency, to size verification services and
+/// catch regressions on the verification path. Only available for tests and
+/// with the `bench-utils` feature, to stay out of production builds.
+#[cfg(any(test, feature = "bench-utils"))]
+pub mod latency;
+
 #[cfg(test)]
 mod tests;
 
 use ::nova::traits::Engine;
-use std::sync::Arc;
+use abomonation::Abomonation;
+use bellpepper::util_cs::{metric_cs::MetricCS, Comparable};
+use bellpepper_core::Circuit;
+use rayon::{prelude::*, ThreadPool};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 use crate::{
+    cli::zstore::ZDag,
+    config::{lurk_config, Settings},
     coprocessor::Coprocessor,
-    error::ProofError,
+    error::{ProofError, ReductionError},
     eval::lang::Lang,
     field::LurkField,
-    lem::{eval::EvalConfig, interpreter::Frame, pointers::Ptr, store::Store},
-    proof::nova::E2,
+    lem::{
+        eval::{make_cprocs_funcs_from_lang, make_eval_step_from_config, EvalConfig},
+        interpreter::Frame,
+        pointers::{Ptr, ZPtr},
+        store::Store,
+        tag::Tag,
+    },
+    proof::nova::{E1, E2},
+    tag::ExprTag,
 };
 
 use self::{
     nova::{CurveCycleEquipped, C1LEM},
-    supernova::FoldingConfig,
+    supernova::{nivc_set_digest, FoldingConfig},
 };
 
 /// The State of a CEK machine.
@@ -96,7 +174,7 @@ where
     type PublicParams;
 
     /// Type for error potentially thrown during verification
-    type ErrorType;
+    type ErrorType: Into<ProofError>;
 
     /// Generate the recursive SNARK, encoded in `ProveOutput`
     fn prove_recursively(
@@ -106,13 +184,635 @@ where
         store: &'a Store<F>,
         reduction_count: usize,
         lang: Arc<Lang<F, C>>,
+    ) -> Result<Self, ProofError> {
+        Self::prove_recursively_in(None, pp, z0, steps, store, reduction_count, lang)
+    }
+
+    /// Generate the recursive SNARK like `prove_recursively`, but running all
+    /// of its parallel work in `pool`, if any, instead of the global rayon pool.
+    /// This caps the threads a proving job can take from concurrent ones.
+    ///
+    /// It covers both the folding done on the calling thread, which is moved to
+    /// `pool`, and the background thread caching witnesses in the parallel path,
+    /// whose `par_iter`s would otherwise run in the global pool.
+    fn prove_recursively_in(
+        pool: Option<&ThreadPool>,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+    ) -> Result<Self, ProofError> {
+        let settings = ProvingSettings::configured();
+        let (proof, _) = Self::prove_recursively_with_settings(
+            pool,
+            &settings,
+            pp,
+            z0,
+            steps,
+            store,
+            reduction_count,
+            lang,
+            &mut |_, _| Ok(()),
+        )?;
+        Ok(proof)
+    }
+
+    /// Generate the recursive SNARK like `prove_recursively_in`, with
+    /// `settings` instead of the ones of the Lurk config, calling
+    /// `on_step(step_index, step)` on each step right before it's folded.
+    /// Returns the path the steps were folded with along with the proof.
+    ///
+    /// On the parallel path, `on_step` is called on steps whose witness may
+    /// have been cached ahead of folding.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_recursively_with_settings(
+        pool: Option<&ThreadPool>,
+        settings: &ProvingSettings,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+        on_step: &mut OnStep<'_, 'a, F, C>,
+    ) -> Result<(Self, FoldingPath), ProofError>;
+
+    /// Generate the recursive SNARK from steps produced lazily, e.g. by an
+    /// evaluator, so they don't need to be materialized all at once. Each step is
+    /// only pulled from `steps` once the previous one has been folded, and the
+    /// first error yielded by `steps` aborts proving.
+    ///
+    /// Unlike `prove_recursively`, this always folds sequentially: witnesses
+    /// can't be cached ahead of time for steps that don't exist yet.
+    fn prove_recursively_lazy<I: IntoIterator<Item = Result<C1LEM<'a, F, C>, ProofError>>>(
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: I,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
     ) -> Result<Self, ProofError>;
 
-    /// Compress a proof
+    /// Generate the recursive SNARK from borrowed steps, so the caller keeps
+    /// them, e.g. to store them for auditing or re-proving.
+    ///
+    /// This folds sequentially, as done by `prove_recursively_lazy`: the
+    /// parallel path of `prove_recursively` caches witnesses in the steps ahead
+    /// of folding, so it needs to own them. Each step is cloned right before it
+    /// gets folded and the clone is dropped right after, so witnesses cached by
+    /// the caller are reused but those computed while folding aren't kept.
+    fn prove_recursively_borrowed(
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: &[C1LEM<'a, F, C>],
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+    ) -> Result<Self, ProofError> {
+        let steps = steps.iter().map(|step| Ok(step.clone()));
+        Self::prove_recursively_lazy(pp, z0, steps, store, reduction_count, lang)
+    }
+
+    /// Generate the recursive SNARK like `prove_recursively`, collecting
+    /// statistics about the folded steps along the way.
+    ///
+    /// The steps are folded on the path set by the Lurk config, which is
+    /// recorded in `ProofStats::folding_path`. Witnesses that haven't been
+    /// cached, by the caller or by the parallel path, are computed right before
+    /// their step is folded. Use `prove_recursively` when the statistics aren't
+    /// needed.
+    fn prove_recursively_with_stats(
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+    ) -> Result<(Self, ProofStats), ProofError> {
+        Self::prove_recursively_with_witness_cap(pp, z0, steps, store, reduction_count, lang, None)
+    }
+
+    /// Generate the recursive SNARK with statistics like
+    /// `prove_recursively_with_stats`, erroring with
+    /// `ProofError::WitnessTooLarge` on the first step whose witness has more
+    /// than `max_witness_size` elements, if set. Each witness is checked as soon
+    /// as it's computed, before it's folded and before the next one is computed,
+    /// so a runaway coprocessor witness is caught while it's the only one held.
+    fn prove_recursively_with_witness_cap(
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+        max_witness_size: Option<usize>,
+    ) -> Result<(Self, ProofStats), ProofError> {
+        Self::prove_recursively_with_stats_and_settings(
+            &ProvingSettings::configured(),
+            pp,
+            z0,
+            steps,
+            store,
+            reduction_count,
+            lang,
+            max_witness_size,
+        )
+    }
+
+    /// Generate the recursive SNARK with statistics like
+    /// `prove_recursively_with_witness_cap`, with `settings` instead of the
+    /// ones of the Lurk config.
+    ///
+    /// Setting `max_witness_size` forces the sequential path, where no witness
+    /// is computed ahead of the step being folded.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_recursively_with_stats_and_settings(
+        settings: &ProvingSettings,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+        max_witness_size: Option<usize>,
+    ) -> Result<(Self, ProofStats), ProofError> {
+        let mut settings = *settings;
+        if max_witness_size.is_some() {
+            settings.folding_path = FoldingPath::Sequential;
+        }
+        let mut stats = ProofStats::default();
+        let mut step_start: Option<Instant> = None;
+        let mut on_step =
+            |step_index: usize, step: &mut C1LEM<'a, F, C>| -> Result<(), ProofError> {
+                let now = Instant::now();
+                if let Some(start) = step_start.replace(now) {
+                    stats.per_step_durations.push(now - start);
+                }
+                stats.num_steps += 1;
+                *stats
+                    .pc_histogram
+                    .entry(step.program_counter())
+                    .or_default() += 1;
+                if step.cached_witness_bytes().is_some() {
+                    stats.cache_hits += 1;
+                } else {
+                    stats.cache_misses += 1;
+                    step.cache_witness_with(store, settings.zeroize_witnesses)?;
+                }
+                let witness_bytes = step.cached_witness_bytes().unwrap_or_default();
+                stats.peak_bytes = stats.peak_bytes.max(witness_bytes);
+                let size = step.cached_witness_len().unwrap_or_default();
+                if max_witness_size.is_some_and(|max| size > max) {
+                    return Err(ProofError::WitnessTooLarge { step_index, size });
+                }
+                stats.witness_sizes.push(size);
+                Ok(())
+            };
+        let (proof, folding_path) = Self::prove_recursively_with_settings(
+            None,
+            &settings,
+            pp,
+            z0,
+            steps,
+            store,
+            reduction_count,
+            lang,
+            &mut on_step,
+        )?;
+        if let Some(start) = step_start {
+            stats.per_step_durations.push(start.elapsed());
+        }
+        let num_threads = u32::try_from(rayon::current_num_threads()).unwrap_or(u32::MAX);
+        stats.estimated_cpu_time = stats.per_step_durations.iter().sum::<Duration>() * num_threads;
+        stats.folding_path = folding_path;
+        Ok((proof, stats))
+    }
+
+    /// Generate the recursive SNARK like `prove_recursively_lazy`, calling
+    /// `on_dispatch(step_index, circuit_index)` for each step folded by the
+    /// circuit of a coprocessor, i.e. with a nonzero program counter, which is
+    /// the index of that circuit. The call is made when the step is handed over
+    /// to be folded, so events stream in folding order while proving, e.g. to
+    /// report progress. `ProofStats::pc_histogram` gives the aggregate instead.
+    fn prove_recursively_with_dispatch_hook<D: FnMut(usize, usize)>(
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+        mut on_dispatch: D,
+    ) -> Result<Self, ProofError> {
+        let steps = steps.into_iter().enumerate().map(|(step_index, step)| {
+            let circuit_index = step.program_counter();
+            if circuit_index != 0 {
+                on_dispatch(step_index, circuit_index);
+            }
+            Ok(step)
+        });
+        Self::prove_recursively_lazy(pp, z0, steps, store, reduction_count, lang)
+    }
+
+    /// Generate the recursive SNARK like `prove_recursively`, rejecting the
+    /// steps folded by circuits outside of `allowed_circuits`, if any, before
+    /// proving anything. Circuits are identified by the program counters of
+    /// the steps, as in `ProofStats::pc_histogram`, so `0` must be allowed for
+    /// anything to be proven.
+    ///
+    /// Errors with `ProofError::DisallowedCoprocessor` at the first step whose
+    /// circuit isn't allowed. In IVC, coprocessors are folded by the Lurk step
+    /// circuit, whose program counter is `0`, so the allowlist can only
+    /// exclude coprocessors in NIVC.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_recursively_with_allowlist(
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+        allowed_circuits: Option<&HashSet<usize>>,
+    ) -> Result<Self, ProofError> {
+        if let Some(allowed_circuits) = allowed_circuits {
+            for (step_index, step) in steps.iter().enumerate() {
+                let pc = step.program_counter();
+                if !allowed_circuits.contains(&pc) {
+                    return Err(ProofError::DisallowedCoprocessor { pc, step_index });
+                }
+            }
+        }
+        Self::prove_recursively(pp, z0, steps, store, reduction_count, lang)
+    }
+
+    /// Compress a proof. Compressed proofs are returned as they are.
     fn compress(self, pp: &Self::PublicParams) -> Result<Self, ProofError>;
 
-    /// Verify the proof given the public parameters, the input and output values
-    fn verify(&self, pp: &Self::PublicParams, z0: &[F], zi: &[F]) -> Result<bool, Self::ErrorType>;
+    /// Compress a proof like `compress`, but error with
+    /// `ProofError::AlreadyCompressed` if it's already compressed, for callers
+    /// that expect to always be handed a recursive proof
+    fn compress_strict(self, pp: &Self::PublicParams) -> Result<Self, ProofError> {
+        if self.is_compressed() {
+            return Err(ProofError::AlreadyCompressed);
+        }
+        self.compress(pp)
+    }
+
+    /// Compress the proof only if it's larger than `threshold_bytes`, as
+    /// measured by `size_bytes`. Compression always makes recursive proofs
+    /// smaller, but takes time, so small enough proofs are returned as they
+    /// are.
+    fn compress_if_larger_than(
+        self,
+        pp: &Self::PublicParams,
+        threshold_bytes: usize,
+    ) -> Result<Self, ProofError>
+    where
+        Self: Serialize,
+    {
+        if self.size_bytes()? > threshold_bytes {
+            self.compress(pp)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// A digest of the proof serialized with `bincode`, e.g. to refer to it in
+    /// a `VerificationAttestation`
+    fn digest(&self) -> Result<F, ProofError>
+    where
+        Self: Serialize,
+    {
+        Ok(extend_digest(F::ZERO, &bincode::serialize(self)?))
+    }
+
+    /// The size in bytes of the proof serialized with `bincode`.
+    ///
+    /// Note: proofs have no canonical encoding of their own, only their `serde`
+    /// implementations, which follow those of the Nova components. These can't
+    /// be encoded field by field, since `nova` keeps their fields private, so
+    /// stored proofs must be decoded with the `nova` release they were
+    /// serialized with.
+    fn size_bytes(&self) -> Result<usize, ProofError>
+    where
+        Self: Serialize,
+    {
+        Ok(bincode::serialized_size(self)? as usize)
+    }
+
+    /// Whether the proof is compressed
+    fn is_compressed(&self) -> bool;
+
+    /// The folding mode the proof was produced under, as recorded in the proof
+    fn folding_mode(&self) -> FoldingMode;
+
+    /// Reproduces a proof from the same public parameters, input and steps used
+    /// to generate `received` and checks that both are equal byte for byte, as
+    /// serialized with `bincode`. The reproduced proof is compressed first if
+    /// `received` is compressed.
+    fn reproduce_and_compare(
+        received: &Self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+    ) -> Result<bool, ProofError>
+    where
+        Self: Serialize,
+    {
+        let mut reproduced = Self::prove_recursively(pp, z0, steps, store, reduction_count, lang)?;
+        if received.is_compressed() {
+            reproduced = reproduced.compress(pp)?;
+        }
+        Ok(bincode::serialize(received)? == bincode::serialize(&reproduced)?)
+    }
+
+    /// Whether the SNARKs backing this proof type support compression, i.e.
+    /// whether `compress` can structurally succeed.
+    ///
+    /// Implementations returning `true` must guarantee that `compress` on a
+    /// recursive proof only fails for runtime reasons (e.g. an unsatisfied
+    /// instance) and that compressing an already compressed proof is a no-op.
+    /// Pipelines can rely on `false` to skip compression altogether.
+    #[inline]
+    fn can_compress() -> bool {
+        true
+    }
+
+    /// Verify the proof given the public parameters, the input and output values,
+    /// reporting which check failed, if any
+    fn verify_detailed(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        zi: &[F],
+    ) -> VerifyOutcome<F, Self::ErrorType>;
+
+    /// Verify the proof given the public parameters and the input, comparing
+    /// its output with the expected one of `options`, if any. Returns the
+    /// output the proof attests if it's valid, and `None` if it isn't, e.g.
+    /// because it attests another output than the expected one.
+    ///
+    /// Errors with `ProofError::ArityMismatch` if `z0` or the expected output
+    /// don't have the arity of the Lurk circuits, before verifying anything,
+    /// and with `ProofError::FoldingModeMismatch` if the proof wasn't folded
+    /// in the mode of `pp`.
+    fn verify_with_options(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        options: &VerifyOptions<F>,
+    ) -> Result<Option<Vec<F>>, ProofError> {
+        let expected = options.expected_output.as_deref();
+        if z0.len() != IO_ARITY || expected.is_some_and(|zi| zi.len() != IO_ARITY) {
+            return Err(ProofError::ArityMismatch {
+                expected: IO_ARITY,
+                z0: z0.len(),
+                zi: expected.map_or(IO_ARITY, <[F]>::len),
+            });
+        }
+        // Without an expected output, the proof is checked against an empty
+        // one, which it can't attest, so the outcome reports the attested one
+        match self.verify_detailed(pp, z0, expected.unwrap_or_default()) {
+            VerifyOutcome::Valid => Ok(expected.map(<[F]>::to_vec)),
+            VerifyOutcome::PrimaryOutputMismatch { got, .. } if expected.is_none() => Ok(Some(got)),
+            VerifyOutcome::CryptographicFailure(e) => Err(e.into()),
+            VerifyOutcome::FoldingModeMismatch { expected, got } => {
+                Err(ProofError::FoldingModeMismatch { expected, got })
+            }
+            VerifyOutcome::PrimaryOutputMismatch { .. }
+            | VerifyOutcome::SecondaryOutputMismatch => Ok(None),
+        }
+    }
+
+    /// Verify the proof given the public parameters, the input and output values.
+    ///
+    /// Errors with `ProofError::ArityMismatch` if `z0` or `zi` don't have the
+    /// arity of the Lurk circuits, before verifying anything, and with
+    /// `ProofError::FoldingModeMismatch` if the proof wasn't folded in the
+    /// mode of `pp`.
+    fn verify(&self, pp: &Self::PublicParams, z0: &[F], zi: &[F]) -> Result<bool, ProofError> {
+        let options = VerifyOptions {
+            expected_output: Some(zi.to_vec()),
+        };
+        Ok(self.verify_with_options(pp, z0, &options)?.is_some())
+    }
+
+    /// Verify the proof like `verify`, with the input and output given as
+    /// hexadecimal field elements, as parsed by `LurkField::from_hex_digits`,
+    /// e.g. when they are passed from a shell or another language.
+    ///
+    /// Errors with `ProofError::InvalidFieldElement` on the first string that
+    /// isn't the hexadecimal encoding of a field element, before verifying
+    /// anything.
+    fn verify_hex(
+        &self,
+        pp: &Self::PublicParams,
+        z0_hex: &[&str],
+        zi_hex: &[&str],
+    ) -> Result<bool, ProofError> {
+        let parse = |hex: &[&str]| {
+            hex.iter()
+                .map(|digits| {
+                    F::from_hex_digits(digits)
+                        .ok_or_else(|| ProofError::InvalidFieldElement(digits.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        self.verify(pp, &parse(z0_hex)?, &parse(zi_hex)?)
+    }
+
+    /// Verify the proof given the public parameters and the input, returning
+    /// the output it attests if it's valid, and `None` otherwise
+    fn verify_and_extract(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+    ) -> Result<Option<Vec<F>>, ProofError> {
+        self.verify_with_options(pp, z0, &VerifyOptions::default())
+    }
+
+    /// Verify the proof once and check that its output is one of
+    /// `acceptable_zis`, e.g. one of several canonical forms of a result,
+    /// instead of verifying it against each of them. An empty set accepts
+    /// nothing, so the proof isn't verified at all.
+    ///
+    /// Errors with `ProofError::ArityMismatch` if `z0` doesn't have the arity
+    /// of the Lurk circuits, and with `ProofError::FoldingModeMismatch` if the
+    /// proof wasn't folded in the mode of `pp`. Outputs of other arities are
+    /// never accepted.
+    fn verify_output_in(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        acceptable_zis: &[Vec<F>],
+    ) -> Result<bool, ProofError> {
+        let Some(first) = acceptable_zis.first() else {
+            return Ok(false);
+        };
+        if z0.len() != IO_ARITY {
+            return Err(ProofError::ArityMismatch {
+                expected: IO_ARITY,
+                z0: z0.len(),
+                zi: first.len(),
+            });
+        }
+        match self.verify_detailed(pp, z0, first) {
+            VerifyOutcome::Valid => Ok(true),
+            VerifyOutcome::PrimaryOutputMismatch { got, .. } => Ok(acceptable_zis.contains(&got)),
+            VerifyOutcome::CryptographicFailure(e) => Err(e.into()),
+            VerifyOutcome::FoldingModeMismatch { expected, got } => {
+                Err(ProofError::FoldingModeMismatch { expected, got })
+            }
+            VerifyOutcome::SecondaryOutputMismatch => Ok(false),
+        }
+    }
+
+    /// The primary output the proof claims, if it can be read without verifying
+    /// the proof. It's only a hint for rejecting proofs early, since nothing
+    /// about it is checked until the proof is verified.
+    #[inline]
+    fn unverified_output(&self) -> Option<&[F]> {
+        None
+    }
+
+    /// Like `verify`, but returning `false` as soon as the output claimed by
+    /// the proof, as given by `unverified_output`, differs from `zi`, before
+    /// any cryptographic verification.
+    ///
+    /// Cryptographic verification dominates the latency of `verify`: a recursive
+    /// proof is checked by testing the satisfiability of its running instances,
+    /// which is linear in the size of the circuits, and a compressed one by
+    /// verifying its SNARKs. Rejecting a proof for the wrong output then costs
+    /// next to nothing instead. Valid proofs and proofs failing cryptographic
+    /// verification take as long as with `verify`, as do compressed Nova
+    /// proofs, which don't expose their output. Use `verify_detailed` to get the
+    /// details of every failure.
+    ///
+    /// The output of SuperNova proofs is read from their metadata, which isn't
+    /// bound by the SNARK, so a valid SNARK carrying tampered metadata may be
+    /// rejected here while `verify` would accept it.
+    fn verify_early_exit(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        zi: &[F],
+    ) -> Result<bool, ProofError> {
+        if zi.len() == IO_ARITY && self.unverified_output().is_some_and(|got| got != zi) {
+            return Ok(false);
+        }
+        self.verify(pp, z0, zi)
+    }
+
+    /// Verify a proof that the computation starting at `z0` terminated in a Lurk
+    /// error, e.g. a division by zero: the continuation of `zi` must be the
+    /// error continuation. The expression and environment of `zi` are those the
+    /// evaluation stopped at, which are checked as in `verify`. A valid proof
+    /// thus attests that the program provably errored, since the circuits only
+    /// reach the error continuation on the same errors as the evaluator.
+    fn verify_error(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        zi: &[F],
+    ) -> Result<bool, ProofError> {
+        let store = Store::<F>::default();
+        if zi.get(4..) != Some(&store.to_scalar_vector(&[store.cont_error()])[..]) {
+            return Ok(false);
+        }
+        self.verify(pp, z0, zi)
+    }
+
+    /// Verify a proof of a state transition, as generated by
+    /// `Prover::prove_state_transition`, only checking the new state against
+    /// `expected_commitment`. The output of such a proof is laid out as:
+    /// - `zi[0..2]`: the `Comm` tag and the commitment to the new state, the
+    ///   only position the verifier has to know;
+    /// - `zi[2..4]`: the environment the evaluation ended in, whatever it is;
+    /// - `zi[4..6]`: the terminal continuation.
+    ///
+    /// The output is the one `verify_and_extract` gets from verifying the
+    /// proof, so compressed proofs, which don't claim their output, are checked
+    /// like recursive ones. Errors with `ProofError::NoVerifiedOutput` if
+    /// verifying the proof doesn't yield the output it attests.
+    fn verify_output_commitment(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        expected_commitment: F,
+    ) -> Result<bool, ProofError> {
+        let zi = self
+            .verify_and_extract(pp, z0)?
+            .ok_or(ProofError::NoVerifiedOutput)?;
+        Ok(is_output_commitment(&zi, expected_commitment))
+    }
+
+    /// Verify a proof of the evaluation of `((open <commitment>))`, i.e. of the
+    /// call of a zero-argument function the verifier only knows through its
+    /// commitment, as laid out by `committed_input`. This lets the prover keep
+    /// the program and its inputs hidden while the proof still binds them.
+    ///
+    /// The input layout is the regular one, so the circuits aren't aware of
+    /// the commitment: they open it while evaluating `open`. Hence:
+    /// - the committed payload must be the function itself, e.g. the result of
+    ///   `(hide <secret> (lambda () <program>))`, and not a quoted expression;
+    /// - evaluation starts in the empty environment, from the outermost
+    ///   continuation;
+    /// - the commitment only hides its payload if its secret is random, since
+    ///   commitments made with `commit` can be opened by brute force when the
+    ///   payload is guessable.
+    fn verify_committed_input(
+        &self,
+        pp: &Self::PublicParams,
+        z0_commitment: F,
+        zi: &[F],
+    ) -> Result<bool, ProofError> {
+        let z0 = committed_input(&Store::<F>::default(), z0_commitment);
+        self.verify(pp, &z0, zi)
+    }
+
+    /// Verify a proof generated by `Prover::prove_committed_program`, i.e. that
+    /// the program hidden behind `program_commitment` was run on `input`, with
+    /// the output `zi`. `store` must know `input`, but not the program.
+    fn verify_committed_program(
+        &self,
+        pp: &Self::PublicParams,
+        program_commitment: F,
+        input: Ptr,
+        store: &Store<F>,
+        zi: &[F],
+    ) -> Result<bool, ProofError> {
+        let z0 = committed_program_input(store, program_commitment, input);
+        self.verify(pp, &z0, zi)
+    }
+
+    /// Verify a proof whose output expression is only partially disclosed. The
+    /// program must return a list, as laid out by `disclosed_output`, whose
+    /// elements are each either revealed or hidden behind a commitment, e.g.
+    /// `(list <public> (hide <secret> <private>))`. The expression part of `zi`
+    /// must match the disclosure, and the rest of `zi`, i.e. the output
+    /// environment and continuation, is checked as in `verify`.
+    ///
+    /// The circuits hash the output list out of the hashes of its elements, so
+    /// a hidden element binds the value that was committed to while evaluating
+    /// the program, without revealing it. As with `verify_committed_input`, the
+    /// commitments only hide their payloads if their secrets are random.
+    fn verify_disclosed_output(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        zi: &[F],
+        store: &Store<F>,
+        disclosed: &[Disclosed<F>],
+    ) -> Result<bool, ProofError> {
+        if zi.get(..2) != Some(&disclosed_output(store, disclosed)[..]) {
+            return Ok(false);
+        }
+        self.verify(pp, z0, zi)
+    }
 
     /// Return the `z0_secondary`
     #[inline]
@@ -120,10 +820,914 @@ where
         use ff::Field;
         vec![<E2<F> as Engine>::Scalar::ZERO]
     }
+
+    /// Generate a chain of compressed proofs, compressing the running proof
+    /// every `compress_every` steps and folding the remaining steps into a fresh
+    /// recursive instance seeded by the output of the previous segment. With
+    /// `None`, a single segment covering all the `steps` is produced.
+    ///
+    /// Only one segment is ever being folded at a time, so the memory used by
+    /// the recursive proof stays bounded regardless of the number of steps.
+    ///
+    /// The resulting chain is verified with `verify_chain`, which checks that:
+    /// - the first segment starts at the claimed `z0`;
+    /// - each segment starts where the previous one ended;
+    /// - each segment verifies against its own input and output;
+    /// - the last segment ends at the claimed `zi`.
+    ///
+    /// Errors with `ProofError::MissingOutput` if the last step of a segment
+    /// has no output to seed the next one with, e.g. a blank step.
+    fn prove_recursively_chained(
+        pp: &Self::PublicParams,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+        compress_every: Option<usize>,
+    ) -> Result<Vec<ChainedSegment<Self, F>>, ProofError> {
+        let segment_len = compress_every.unwrap_or(steps.len()).max(1);
+        let mut segments = Vec::with_capacity((steps.len() + segment_len - 1) / segment_len);
+        let mut segment_z0 = z0.to_vec();
+        let mut steps = steps.into_iter().peekable();
+        let mut num_steps = 0;
+        while steps.peek().is_some() {
+            let segment_steps = steps.by_ref().take(segment_len).collect::<Vec<_>>();
+            num_steps += segment_steps.len();
+            let last_output = segment_steps
+                .last()
+                .and_then(|step| step.output().as_ref())
+                .ok_or(ProofError::MissingOutput {
+                    step_index: num_steps - 1,
+                })?;
+            let segment_zi = store.to_scalar_vector(last_output);
+            let proof = Self::prove_recursively(
+                pp,
+                &segment_z0,
+                segment_steps,
+                store,
+                reduction_count,
+                lang.clone(),
+            )?
+            .compress(pp)?;
+            segments.push(ChainedSegment {
+                proof,
+                z0: segment_z0,
+                zi: segment_zi.clone(),
+            });
+            segment_z0 = segment_zi;
+        }
+        Ok(segments)
+    }
+
+    /// Verify a chain of proofs generated by `prove_recursively_chained` given
+    /// the public parameters and the input and output values of the whole chain
+    fn verify_chain(
+        segments: &[ChainedSegment<Self, F>],
+        pp: &Self::PublicParams,
+        z0: &[F],
+        zi: &[F],
+    ) -> Result<bool, ProofError> {
+        let mut expected_z0 = z0;
+        for segment in segments {
+            if segment.z0 != expected_z0 || !segment.proof.verify(pp, &segment.z0, &segment.zi)? {
+                return Ok(false);
+            }
+            expected_z0 = &segment.zi;
+        }
+        Ok(!segments.is_empty() && expected_z0 == zi)
+    }
+
+    /// Verify the first `num_segments` segments of a chain generated by
+    /// `prove_recursively_chained`, as if proving had stopped there, given the
+    /// input of the chain and the output `zi_at_segment` of the last of these
+    /// segments.
+    ///
+    /// Prefixes are counted in segments, not in steps: a recursive proof only
+    /// attests the state after its last folded step, so the chain can only be
+    /// checked up to the boundaries of its segments. Verifying at a finer
+    /// granularity requires proving the chain with shorter segments.
+    fn verify_chain_segments(
+        segments: &[ChainedSegment<Self, F>],
+        pp: &Self::PublicParams,
+        z0: &[F],
+        zi_at_segment: &[F],
+        num_segments: usize,
+    ) -> Result<bool, ProofError> {
+        match segments.get(..num_segments) {
+            Some(prefix) => Self::verify_chain(prefix, pp, z0, zi_at_segment),
+            None => Ok(false),
+        }
+    }
 }
 
-/// Folding mode used for proving
+/// The memory available on the host, in bytes, as the kernel estimates it for
+/// starting new work without swapping. Read from `MemAvailable` in
+/// `/proc/meminfo`, so it's only known on Linux.
+pub fn available_memory_bytes() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kib: usize = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    kib.checked_mul(1024)
+}
+
+/// Whether the memory available on the host is below the soft budget
+/// `min_available_memory_bytes`, as set in the Lurk config.
+///
+/// Proving in parallel samples it before caching each witness ahead of folding
+/// and after folding each step. The degradation is gradual and reversible:
+/// - while under pressure, witnesses aren't cached ahead anymore, so the
+///   remaining steps have their witnesses computed on demand while folding,
+///   as in sequential proving;
+/// - folded steps drop their cached witnesses, releasing the memory of the
+///   ones cached before the pressure arose;
+/// - once memory is available again, caching resumes with the next step.
+///
+/// It's never under pressure if no budget is set or if the available memory
+/// can't be read, e.g. outside of Linux.
+pub(crate) fn under_memory_pressure(min_available_memory_bytes: Option<usize>) -> bool {
+    min_available_memory_bytes
+        .is_some_and(|min| available_memory_bytes().is_some_and(|available| available < min))
+}
+
+/// Hashes `digest` along with `extra` with SHA-256: the bytes of `digest` are
+/// absorbed first, followed by the length of `extra` and its bytes. The hash is
+/// truncated to 248 bits, so that it fits in the field of any supported curve.
+fn extend_digest<F: LurkField>(digest: F, extra: &[u8]) -> F {
+    let mut hasher = Sha256::new();
+    hasher.update(digest.to_bytes());
+    hasher.update((extra.len() as u64).to_le_bytes());
+    hasher.update(extra);
+    let hash = hasher.finalize();
+    let mut bytes = F::ZERO.to_bytes();
+    bytes[..31].copy_from_slice(&hash[..31]);
+    F::from_bytes(&bytes).expect("248 bits fit in the field")
+}
+
+/// Number of elements in the public input and output of the Lurk circuits: the
+/// tags and values of an expression, an environment and a continuation
+///
+/// The IO isn't hashed by Lurk: the tags and values are passed to the folding
+/// scheme as they are, and the values are already hashes, computed by the
+/// store with the Poseidon arity fixed by the tag of each pointer, e.g. 4 for
+/// conses. The circuits constrain those same hashes when destructuring the
+/// pointers, so there is no arity to configure for the IO alone. The only hash
+/// of the IO is the one of `nova` itself, which absorbs it into its random
+/// oracle with constants fixed by the curve cycle, and isn't configurable
+/// either. Changing an arity means changing how the store hashes data, i.e.
+/// the tags and the circuits using them, which is reflected by the `Lang`
+/// digest and hence by `circuit_cache_key`.
+const IO_ARITY: usize = 6;
+
+/// A segment of a chained proof, along with the input and output values it was
+/// proven against
+#[derive(Serialize, Deserialize)]
+pub struct ChainedSegment<P, F> {
+    /// The compressed proof for this segment
+    pub proof: P,
+    /// The input of the segment, which is the output of the previous one
+    pub z0: Vec<F>,
+    /// The output of the segment
+    pub zi: Vec<F>,
+}
+
+/// Verifies the segments of a chain generated by
+/// `RecursiveSNARKTrait::prove_recursively_chained` one at a time, as they
+/// arrive, keeping track of the output of the chain so far. Unlike
+/// `RecursiveSNARKTrait::verify_chain`, the segments don't need to be buffered.
+pub struct ChainVerifier<'p, 'a, F, C, P>
+where
+    F: CurveCycleEquipped,
+    C: Coprocessor<F> + 'a,
+    P: RecursiveSNARKTrait<'a, F, C>,
+{
+    pp: &'p P::PublicParams,
+    zi: Vec<F>,
+    num_chunks: usize,
+    _p: PhantomData<(&'a C, P)>,
+}
+
+impl<'p, 'a, F, C, P> ChainVerifier<'p, 'a, F, C, P>
+where
+    F: CurveCycleEquipped,
+    C: Coprocessor<F> + 'a,
+    P: RecursiveSNARKTrait<'a, F, C>,
+{
+    /// Starts verifying a chain with input `z0`
+    pub fn new(pp: &'p P::PublicParams, z0: &[F]) -> Self {
+        Self {
+            pp,
+            zi: z0.to_vec(),
+            num_chunks: 0,
+            _p: PhantomData,
+        }
+    }
+
+    /// Verifies the next segment of the chain, which must start at the output of
+    /// the previous one. On error, the segment is discarded and the chain is
+    /// left as it was, so the right segment can still be pushed afterwards.
+    pub fn push(&mut self, segment: &ChainedSegment<P, F>) -> Result<(), ProofError> {
+        let index = self.num_chunks;
+        if segment.z0 != self.zi {
+            return Err(ProofError::ChunkInputMismatch { index });
+        }
+        if !segment.proof.verify(self.pp, &segment.z0, &segment.zi)? {
+            return Err(ProofError::InvalidChunk { index });
+        }
+        self.zi.clone_from(&segment.zi);
+        self.num_chunks += 1;
+        Ok(())
+    }
+
+    /// Returns the number of segments verified so far
+    #[inline]
+    pub fn num_chunks(&self) -> usize {
+        self.num_chunks
+    }
+
+    /// Returns the output of the chain verified so far
+    #[inline]
+    pub fn current_output(&self) -> &[F] {
+        &self.zi
+    }
+
+    /// Returns the output of the verified chain, which is its input if no
+    /// segment was pushed
+    #[inline]
+    pub fn finalize(self) -> Vec<F> {
+        self.zi
+    }
+}
+
+/// A compact and serializable trace of the frames of an evaluation, from which
+/// they can be rebuilt against a `Store` to be proven later on. This decouples
+/// the generation of traces from proving.
+///
+/// Only the input and the program counter of each frame are recorded, along
+/// with the data reachable from the inputs. Frames are deterministic functions
+/// of those, so rebuilding them amounts to running one reduction per frame.
+/// The store used to rebuild the frames:
+/// - gets populated with the recorded data, so it doesn't need to be the one
+///   the trace was generated with;
+/// - must have the commitments opened by the computation added to it, since
+///   the trace doesn't carry their secrets;
+/// - must be the one used for proving the rebuilt frames.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FrameTrace<F: LurkField> {
+    z_dag: ZDag<F>,
+    inputs: Vec<(Vec<ZPtr<F>>, usize)>,
+}
+
+impl<F: LurkField> FrameTrace<F> {
+    /// Records the frames of an evaluation performed with `store`
+    pub fn new(frames: &[Frame], store: &Store<F>) -> Self {
+        let mut z_dag = ZDag::default();
+        let mut cache = HashMap::default();
+        let mut inputs = Vec::with_capacity(frames.len());
+        for frame in frames {
+            let input = frame
+                .input
+                .iter()
+                .map(|ptr| z_dag.populate_with(ptr, store, &mut cache))
+                .collect();
+            inputs.push((input, frame.pc));
+        }
+        Self { z_dag, inputs }
+    }
+
+    /// Returns the number of frames in the trace
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Returns `true` if the trace has no frames
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Returns the public input of the traced computation, if any
+    pub fn z0(&self) -> Option<Vec<F>> {
+        self.inputs.first().map(|(input, _)| {
+            input
+                .iter()
+                .flat_map(|z_ptr| [z_ptr.tag_field(), *z_ptr.value()])
+                .collect()
+        })
+    }
+
+    /// Rebuilds the traced frames in `store`, reducing them as configured by
+    /// `eval_config`, which must match the configuration used for the trace
+    pub fn rebuild_frames<C: Coprocessor<F>>(
+        &self,
+        store: &Store<F>,
+        eval_config: &EvalConfig<'_, F, C>,
+    ) -> Result<Vec<Frame>, ProofError> {
+        let lang = eval_config.lang();
+        let lurk_step = make_eval_step_from_config(eval_config);
+        let cprocs = make_cprocs_funcs_from_lang(lang);
+        let mut cache = HashMap::default();
+        let mut frames = Vec::with_capacity(self.inputs.len());
+        for (input, pc) in &self.inputs {
+            let input = input
+                .iter()
+                .map(|z_ptr| self.z_dag.populate_store(z_ptr, store, &mut cache))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .map_err(|e| ReductionError::Misc(e.to_string()))?;
+            let func = if *pc == 0 {
+                &lurk_step
+            } else {
+                cprocs.get(pc - 1).ok_or_else(|| {
+                    ReductionError::Misc(format!("Program counter {pc} outside range"))
+                })?
+            };
+            let frame = func
+                .call_simple(&input, store, lang, *pc)
+                .map_err(|e| ReductionError::Misc(e.to_string()))?;
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+}
+
+/// A statement that the proof with the digest `proof_digest` was found valid,
+/// or not, for the input `z0` and the output `zi` with the public parameters of
+/// digest `params_digest`. It's produced by `verify_and_attest` and meant to be
+/// signed by the verifier, which is left to the caller.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationAttestation<F> {
+    /// The digest of the verified proof, see `RecursiveSNARKTrait::digest`
+    pub proof_digest: F,
+    /// The digest of the public parameters used for verification
+    pub params_digest: F,
+    /// The input the proof was verified against
+    pub z0: Vec<F>,
+    /// The output the proof was verified against
+    pub zi: Vec<F>,
+    /// Whether the proof is valid
+    pub valid: bool,
+}
+
+impl<F: LurkField> VerificationAttestation<F> {
+    /// The canonical encoding of the attestation, to be signed. It's the
+    /// concatenation of:
+    /// - the representations of `proof_digest` and `params_digest`;
+    /// - the length of `z0` as a little-endian `u64`, then the representations
+    ///   of its elements, and the same for `zi`;
+    /// - `1` if the proof is valid and `0` otherwise, as a byte.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.proof_digest.to_bytes();
+        bytes.extend(self.params_digest.to_bytes());
+        for io in [&self.z0, &self.zi] {
+            bytes.extend((io.len() as u64).to_le_bytes());
+            for f in io {
+                bytes.extend(f.to_bytes());
+            }
+        }
+        bytes.push(u8::from(self.valid));
+        bytes
+    }
+}
+
+/// Options of `RecursiveSNARKTrait::verify_with_options`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifyOptions<F> {
+    /// The output the proof must attest, if any. Without it, the proof is only
+    /// checked to be valid, whatever its output.
+    pub expected_output: Option<Vec<F>>,
+}
+
+/// The outcome of verifying a proof
 #[derive(Debug)]
+pub enum VerifyOutcome<F, E> {
+    /// The proof is valid for the given input and output
+    Valid,
+    /// The proof was produced under a folding mode other than the one of the
+    /// public parameters, so it wasn't checked any further
+    FoldingModeMismatch {
+        /// The folding mode of the public parameters
+        expected: FoldingMode,
+        /// The folding mode recorded in the proof
+        got: FoldingMode,
+    },
+    /// The proof is valid, but for a primary output different from the expected one
+    PrimaryOutputMismatch {
+        /// The output the proof was verified against
+        expected: Vec<F>,
+        /// The output the proof actually attests
+        got: Vec<F>,
+    },
+    /// The proof is valid, but for an unexpected secondary output. This is only
+    /// checked for nontrivial secondary circuits, so it isn't reported for now.
+    SecondaryOutputMismatch,
+    /// The proof itself failed to verify
+    CryptographicFailure(E),
+}
+
+impl<F: PartialEq, E> VerifyOutcome<F, E> {
+    /// Compares the outputs attested by a proof with the expected ones. The
+    /// primary outputs are checked first.
+    ///
+    /// The secondary outputs, given as the expected and the attested ones, are
+    /// only needed for a nontrivial secondary circuit. The trivial one copies
+    /// its input to its output and Nova checks it's satisfied when verifying
+    /// the proof, so a verified proof can only attest its secondary input.
+    fn from_outputs<S: PartialEq>(
+        expected: &[F],
+        got: Vec<F>,
+        secondary: Option<(&[S], &[S])>,
+    ) -> Self {
+        if expected != got.as_slice() {
+            Self::PrimaryOutputMismatch {
+                expected: expected.to_vec(),
+                got,
+            }
+        } else if secondary.is_some_and(|(expected, got)| expected != got) {
+            Self::SecondaryOutputMismatch
+        } else {
+            Self::Valid
+        }
+    }
+
+    /// Returns `true` on `VerifyOutcome::Valid`
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+}
+
+/// Sizes of the circuits covered by a compressed SNARK, which indicate the
+/// expected cost of its verification
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressedStats {
+    /// Number of constraints of the primary circuits
+    pub primary_constraints: usize,
+    /// Number of variables of the primary circuits
+    pub primary_variables: usize,
+    /// Number of constraints of the secondary circuit
+    pub secondary_constraints: usize,
+    /// Number of variables of the secondary circuit
+    pub secondary_variables: usize,
+}
+
+/// Structural metadata of a proof, to diagnose proofs that fail to verify or to
+/// deserialize without sharing them, e.g. in a bug report. It only holds
+/// sizes, counts and digests, so it reveals nothing of the folded witnesses.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProofDebugInfo<F: LurkField> {
+    /// Whether the proof is compressed
+    pub compressed: bool,
+    /// The folding mode recorded in the proof
+    pub folding_mode: FoldingMode,
+    /// Number of folded steps recorded in the proof
+    pub num_steps: usize,
+    /// Number of elements of the output carried by the proof, if it carries
+    /// one. It's `IO_ARITY` for proofs of the Lurk circuits.
+    pub output_arity: Option<usize>,
+    /// Size in bytes of the proof serialized with `bincode`
+    pub size_bytes: usize,
+    /// Size in bytes of the SNARK within the proof, serialized with `bincode`
+    pub snark_bytes: usize,
+    /// Digest of the proof, as given by `RecursiveSNARKTrait::digest`
+    pub digest: F,
+    /// Digest of the SNARK alone, which tells a corrupted SNARK apart from
+    /// corrupted metadata when compared with the dump of the original proof
+    pub snark_digest: F,
+}
+
+/// Errors with `ProofError::ReductionCountMismatch` if steps of the reduction
+/// count `rc` can't be folded with parameters generated for `params_rc`
+fn check_reduction_count(params_rc: usize, rc: usize) -> Result<(), ProofError> {
+    if params_rc != rc {
+        return Err(ProofError::ReductionCountMismatch { params_rc, rc });
+    }
+    Ok(())
+}
+
+/// Whether `zi` is the output of an evaluation that terminated with the
+/// commitment `commitment`, as laid out for
+/// `RecursiveSNARKTrait::verify_output_commitment`
+fn is_output_commitment<F: LurkField>(zi: &[F], commitment: F) -> bool {
+    let store = Store::<F>::default();
+    let expr = store.to_scalar_vector(&[store.comm(commitment)]);
+    let cont = store.to_scalar_vector(&[store.cont_terminal()]);
+    zi.len() == IO_ARITY && zi[..2] == expr[..] && zi[4..] == cont[..]
+}
+
+/// The public input `z0` of the evaluation of `((open <commitment>))` in the
+/// empty environment, from the outermost continuation. See
+/// `RecursiveSNARKTrait::verify_committed_input`.
+pub fn committed_input<F: LurkField>(store: &Store<F>, commitment: F) -> Vec<F> {
+    let open = store.list(vec![
+        store.intern_lurk_symbol("open"),
+        store.comm(commitment),
+    ]);
+    let expr = store.list(vec![open]);
+    store.to_scalar_vector(&[expr, store.intern_empty_env(), store.cont_outermost()])
+}
+
+/// The call `((open <program_commitment>) <input>)` of the program hidden
+/// behind `program_commitment` on `input`
+fn committed_program_call<F: LurkField>(
+    store: &Store<F>,
+    program_commitment: F,
+    input: Ptr,
+) -> Ptr {
+    let open = store.list(vec![
+        store.intern_lurk_symbol("open"),
+        store.comm(program_commitment),
+    ]);
+    store.list(vec![open, input])
+}
+
+/// The public input `z0` of the evaluation of `((open <program_commitment>)
+/// <input>)` in the empty environment, from the outermost continuation. See
+/// `Prover::prove_committed_program`.
+pub fn committed_program_input<F: LurkField>(
+    store: &Store<F>,
+    program_commitment: F,
+    input: Ptr,
+) -> Vec<F> {
+    let expr = committed_program_call(store, program_commitment, input);
+    store.to_scalar_vector(&[expr, store.intern_empty_env(), store.cont_outermost()])
+}
+
+/// An item of a session proven by `Prover::prove_session`, as entered in a REPL
+#[derive(Clone, Copy, Debug)]
+pub enum SessionItem {
+    /// An expression, evaluated in the environment of the session
+    Eval(Ptr),
+    /// A non-recursive binding of a symbol to the value of an expression, like
+    /// `!(def <symbol> <expr>)`, extending the environment of the next items
+    Def(Ptr, Ptr),
+    /// A recursive binding of a symbol to the value of an expression, like
+    /// `!(defrec <symbol> <expr>)`, extending the environment of the next items
+    Defrec(Ptr, Ptr),
+}
+
+/// The program evaluating the items of a session in order, each in the
+/// environment the previous ones left, and returning the pair of the result of
+/// the last item and the final environment. Bindings expand to `let` and
+/// `letrec` around the following items, like the `def` and `defrec` meta
+/// commands do, and the result of a binding is its symbol.
+fn session_program<F: LurkField>(store: &Store<F>, items: &[SessionItem]) -> Ptr {
+    let sym = |name| store.intern_lurk_symbol(name);
+    let finish = |result| {
+        let current_env = store.list(vec![sym("current-env")]);
+        store.list(vec![sym("cons"), result, current_env])
+    };
+    let bind = |op, name, expr, body| {
+        let bindings = store.list(vec![store.list(vec![name, expr])]);
+        store.list(vec![sym(op), bindings, body])
+    };
+    let quote = |ptr| store.list(vec![sym("quote"), ptr]);
+    let Some((last, init)) = items.split_last() else {
+        return finish(store.intern_nil());
+    };
+    let last = match *last {
+        SessionItem::Eval(expr) => finish(expr),
+        SessionItem::Def(name, expr) => bind("let", name, expr, finish(quote(name))),
+        SessionItem::Defrec(name, expr) => bind("letrec", name, expr, finish(quote(name))),
+    };
+    init.iter().rev().fold(last, |rest, item| match *item {
+        SessionItem::Eval(expr) => store.list(vec![sym("begin"), expr, rest]),
+        SessionItem::Def(name, expr) => bind("let", name, expr, rest),
+        SessionItem::Defrec(name, expr) => bind("letrec", name, expr, rest),
+    })
+}
+
+/// The public input `z0` of the proof of the session `items` from `env`, which
+/// commits to the initial environment and to all of the items. See
+/// `Prover::prove_session`.
+pub fn session_input<F: LurkField>(store: &Store<F>, env: Ptr, items: &[SessionItem]) -> Vec<F> {
+    let expr = session_program(store, items);
+    store.to_scalar_vector(&[expr, env, store.cont_outermost()])
+}
+
+/// The expression part of the public output `zi` of a session that ended with
+/// `result` in the environment `env`. See `Prover::prove_session`.
+pub fn session_output<F: LurkField>(store: &Store<F>, result: Ptr, env: Ptr) -> Vec<F> {
+    store.to_scalar_vector(&[store.cons(result, env)])
+}
+
+/// An element of a list output by a proven program, as disclosed to the verifier
+#[derive(Clone, Copy, Debug)]
+pub enum Disclosed<F> {
+    /// The element itself, interned in the verifier's store
+    Clear(Ptr),
+    /// The commitment hiding the element
+    Hidden(F),
+}
+
+/// The expression part of the public output `zi` of a program returning the
+/// list of the `disclosed` elements, where hidden elements are commitments. See
+/// `RecursiveSNARKTrait::verify_disclosed_output`.
+pub fn disclosed_output<F: LurkField>(store: &Store<F>, disclosed: &[Disclosed<F>]) -> Vec<F> {
+    let elts = disclosed
+        .iter()
+        .map(|elt| match elt {
+            Disclosed::Clear(ptr) => *ptr,
+            Disclosed::Hidden(commitment) => store.comm(*commitment),
+        })
+        .collect();
+    store.to_scalar_vector(&[store.list(elts)])
+}
+
+/// Statistics collected by `RecursiveSNARKTrait::prove_recursively_with_stats`
+///
+/// These don't include metrics on the accumulated relaxed R1CS error term,
+/// such as the norm of the error vector `E` or its commitment. `nova` keeps the
+/// running relaxed instances and witnesses of its `RecursiveSNARK`s private, so
+/// they can't be read between steps. Besides, `E` lives in the scalar field,
+/// where magnitudes aren't meaningful, and its commitment is a curve point that
+/// only supports equality checks. Folding correctness is instead checked by
+/// verifying the proof.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStats {
+    /// Number of folded steps
+    pub num_steps: usize,
+    /// Number of folded steps per program counter, where `0` is the Lurk step
+    /// circuit and `i > 0` is the circuit of the `i - 1`-th coprocessor
+    pub pc_histogram: BTreeMap<usize, usize>,
+    /// Time spent on each step, in folding order, from the computation of its
+    /// witness to the end of its folding. Reading the clock twice per step is
+    /// negligible next to folding.
+    pub per_step_durations: Vec<Duration>,
+    /// Estimated CPU time of the job, not a measurement: the wall-clock time
+    /// spent on steps multiplied by the number of threads of the rayon pool
+    /// folding them. It assumes the threads are busy throughout, so it's an
+    /// upper bound meant to compare workloads and reduction counts, e.g. as an
+    /// energy proxy.
+    pub estimated_cpu_time: Duration,
+    /// Number of steps whose witness had already been cached, by the caller or
+    /// by the parallel path
+    pub cache_hits: usize,
+    /// Number of steps whose witness had to be computed before folding them
+    pub cache_misses: usize,
+    /// Size in bytes of the largest witness of a step. On the sequential path,
+    /// only one step is held at a time, so this is the peak memory used by
+    /// witnesses on our side, not accounting for the memory used by the folding
+    /// scheme itself. The parallel path holds the witnesses cached ahead of
+    /// folding as well.
+    pub peak_bytes: usize,
+    /// Number of elements of the witness of each step, in folding order
+    pub witness_sizes: Vec<usize>,
+    /// The path the steps were folded with. `prove_recursively` logs the path
+    /// it takes at the debug level instead.
+    pub folding_path: FoldingPath,
+}
+
+/// The code path steps are folded with by `RecursiveSNARKTrait::prove_recursively`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FoldingPath {
+    /// Each step is folded right after the computation of its witness
+    #[default]
+    Sequential,
+    /// The witnesses are cached in a thread of their own while the steps are
+    /// folded
+    Parallel,
+}
+
+impl FoldingPath {
+    /// The path set by `perf.parallelism.recursive_steps` in the Lurk config
+    #[inline]
+    pub fn configured() -> Self {
+        Self::of(lurk_config(None, None))
+    }
+
+    fn of(settings: &Settings) -> Self {
+        if settings.perf.parallelism.recursive_steps.is_parallel() {
+            Self::Parallel
+        } else {
+            Self::Sequential
+        }
+    }
+}
+
+/// The settings of the Lurk config that folding depends on. They are read once
+/// per proving call, by `configured`, and passed down to the folding loop
+/// instead of being read again for every step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProvingSettings {
+    /// The path the steps are folded with
+    pub folding_path: FoldingPath,
+    /// See `config::Settings::zeroize_witnesses`
+    pub zeroize_witnesses: bool,
+    /// See `config::Settings::witness_cache_limit_bytes`
+    pub witness_cache_limit_bytes: Option<usize>,
+    /// See `config::Settings::min_available_memory_bytes`
+    pub min_available_memory_bytes: Option<usize>,
+}
+
+impl ProvingSettings {
+    /// The settings of the Lurk config
+    pub fn configured() -> Self {
+        let settings = lurk_config(None, None);
+        Self {
+            folding_path: FoldingPath::of(settings),
+            zeroize_witnesses: settings.zeroize_witnesses,
+            witness_cache_limit_bytes: settings.witness_cache_limit_bytes,
+            min_available_memory_bytes: settings.min_available_memory_bytes,
+        }
+    }
+
+    /// Whether the host is under memory pressure, see `under_memory_pressure`
+    #[inline]
+    pub(crate) fn under_memory_pressure(&self) -> bool {
+        under_memory_pressure(self.min_available_memory_bytes)
+    }
+}
+
+/// A callback run on each step right before it's folded, given its index, as
+/// taken by `RecursiveSNARKTrait::prove_recursively_with_settings`
+pub type OnStep<'s, 'a, F, C> =
+    dyn FnMut(usize, &mut C1LEM<'a, F, C>) -> Result<(), ProofError> + Send + 's;
+
+/// Folds `steps` in order with `fold`, on the path set by `settings`, calling
+/// `on_step` on each step before folding it. Returns the path taken.
+///
+/// On the parallel path, the witnesses of the next steps are cached in a thread
+/// of its own, in `pool` if any, while the current step is folded. Each step is
+/// taken out of its slot before being folded, so no lock is held by the folding
+/// thread: rayon may run the caching job on the very worker that folds, while
+/// that worker waits for a job of its own inside `prove_step`. Steps that have
+/// already been taken are skipped by the caching thread, which stops as soon as
+/// folding is over, successfully or not.
+pub(crate) fn fold_steps<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
+    pool: Option<&ThreadPool>,
+    settings: &ProvingSettings,
+    steps: Vec<C1LEM<'a, F, C>>,
+    store: &'a Store<F>,
+    on_step: &mut OnStep<'_, 'a, F, C>,
+    mut fold: impl FnMut(usize, &C1LEM<'a, F, C>) -> Result<(), ProofError>,
+) -> Result<FoldingPath, ProofError> {
+    if settings.folding_path == FoldingPath::Sequential {
+        for (i, mut step) in steps.into_iter().enumerate() {
+            on_step(i, &mut step)?;
+            fold(i, &step)?;
+        }
+        return Ok(FoldingPath::Sequential);
+    }
+
+    let cc = steps
+        .into_iter()
+        .map(|mf| (mf.program_counter() == 0, Mutex::new(Some(mf))))
+        .collect::<Vec<_>>();
+    let folded = AtomicBool::new(false);
+
+    // the witnesses are cached in a thread of their own, which must log under
+    // the span of the caller's job as well
+    let span = tracing::Span::current();
+    crossbeam::thread::scope(|s| {
+        s.spawn(|_| {
+            let _span = span.enter();
+            let cached_bytes = AtomicUsize::new(0);
+            let cache_witness = |mf: &Mutex<Option<C1LEM<'a, F, C>>>| {
+                if folded.load(Ordering::Relaxed) {
+                    return;
+                }
+                if let Some(mf) = mf.lock().unwrap().as_mut() {
+                    if let Err(e) = mf.cache_witness_within(store, &cached_bytes, settings) {
+                        // the witness is computed again while folding, which
+                        // surfaces the error
+                        tracing::warn!("witness caching failed: {e}");
+                    }
+                }
+            };
+            // the witnesses are cached in the job's thread pool, if any
+            let cache_witnesses = || {
+                // Skip the very first circuit's witness, so `prove_step` can begin immediately.
+                // That circuit's witness will not be cached and will just be computed on-demand.
+
+                // There are many MultiFrames with PC = 0, each with several inner frames and heavy internal
+                // paralellism for witness generation. So we cache them one at a time.
+                cc.iter()
+                    .skip(1)
+                    .filter(|(is_zero_pc, _)| *is_zero_pc)
+                    .for_each(|(_, mf)| cache_witness(mf));
+
+                // There shouldn't be as many MultiFrames with PC != 0 and they only have one inner frame, each with
+                // poor internal parallelism for witness generation, so we can generate their witnesses in parallel.
+                // TODO: once we have robust benchmarking for NIVC, we should test whether merging this loop with
+                // the non-parallel one above (and getting rid of the filters) is better
+                cc.par_iter()
+                    .skip(1)
+                    .filter(|(is_zero_pc, _)| !*is_zero_pc)
+                    .for_each(|(_, mf)| cache_witness(mf));
+            };
+            match pool {
+                Some(pool) => pool.install(cache_witnesses),
+                None => cache_witnesses(),
+            }
+        });
+
+        let result = cc.iter().enumerate().try_for_each(|(i, (_, mf))| {
+            let mut step = mf.lock().unwrap().take().expect("step folded twice");
+            // the witness is cleared whether folding succeeded or not
+            let result = on_step(i, &mut step).and_then(|()| fold(i, &step));
+            if settings.zeroize_witnesses || settings.under_memory_pressure() {
+                step.clear_cached_witness();
+            }
+            result
+        });
+        folded.store(true, Ordering::Relaxed);
+        result
+    })
+    .unwrap()?;
+    Ok(FoldingPath::Parallel)
+}
+
+impl ProofStats {
+    /// `estimated_cpu_time` in seconds
+    #[inline]
+    pub fn estimated_cpu_seconds(&self) -> f64 {
+        self.estimated_cpu_time.as_secs_f64()
+    }
+}
+
+/// The cost of proving a program in one folding mode, as measured by
+/// `compare_folding_modes`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoldingCost {
+    /// Time spent generating the public parameters
+    pub setup_time: Duration,
+    /// Time spent evaluating the program and folding its steps
+    pub prove_time: Duration,
+    /// Size in bytes of the recursive proof, before compression
+    pub proof_bytes: usize,
+    /// Number of folded steps
+    pub num_steps: usize,
+}
+
+/// The costs of proving the same program in IVC and in NIVC
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoldingComparison {
+    /// The cost of proving with Nova, in IVC
+    pub ivc: FoldingCost,
+    /// The cost of proving with SuperNova, in NIVC
+    pub nivc: FoldingCost,
+}
+
+/// Proves the evaluation of `src` in the empty environment with the reduction
+/// count `rc`, both in IVC and in NIVC, and measures the cost of each.
+///
+/// The public parameters are generated from scratch rather than loaded from the
+/// disk cache, so the setup times are comparable. The two modes don't have any
+/// circuit in common, since IVC inlines the coprocessors in the Lurk step
+/// circuit, so expect this to take as long as two setups and two proofs.
+pub fn compare_folding_modes<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
+    lang: &Arc<Lang<F, C>>,
+    rc: usize,
+    src: &str,
+    store: &'a Store<F>,
+) -> Result<FoldingComparison, ProofError>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    let expr = store
+        .read_with_default_state(src)
+        .map_err(|e| ReductionError::Misc(e.to_string()))?;
+    let env = store.intern_empty_env();
+
+    let start = Instant::now();
+    let pp = nova::public_params(rc, lang.clone());
+    let setup_time = start.elapsed();
+    let start = Instant::now();
+    let prover = nova::NovaProver::new(rc, lang.clone());
+    let (proof, _, _, num_steps) = prover.evaluate_and_prove(&pp, expr, env, store, usize::MAX)?;
+    let prove_time = start.elapsed();
+    let ivc = FoldingCost {
+        setup_time,
+        prove_time,
+        proof_bytes: proof.size_bytes()?,
+        num_steps,
+    };
+
+    let start = Instant::now();
+    let pp = supernova::public_params(rc, lang.clone())?;
+    let setup_time = start.elapsed();
+    let start = Instant::now();
+    let prover = supernova::SuperNovaProver::new(rc, lang.clone());
+    let (proof, _, _, num_steps) = prover.evaluate_and_prove(&pp, expr, env, store, usize::MAX)?;
+    let prove_time = start.elapsed();
+    let nivc = FoldingCost {
+        setup_time,
+        prove_time,
+        proof_bytes: proof.size_bytes()?,
+        num_steps,
+    };
+
+    Ok(FoldingComparison { ivc, nivc })
+}
+
+/// Folding mode used for proving
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FoldingMode {
     /// Variant for IVC folding
     IVC,
@@ -152,6 +1756,237 @@ impl FoldingMode {
             Self::NIVC => EvalConfig::new_nivc(lang),
         }
     }
+
+    /// Returns the largest reduction count for which the circuit folding the
+    /// Lurk reduction has at most `target_constraints` constraints.
+    ///
+    /// The circuit has a fixed overhead plus the same number of constraints for
+    /// each reduction, so the blank circuits are only synthesized for two
+    /// reduction counts. In NIVC, the coprocessor circuits are independent of the
+    /// reduction count and thus not taken into account.
+    ///
+    /// Errors with `ProofError::ConstraintTargetTooLow` if even a reduction
+    /// count of 1 exceeds `target_constraints`.
+    pub fn recommend_reduction_count<F: LurkField, C: Coprocessor<F>>(
+        &self,
+        lang: &Arc<Lang<F, C>>,
+        target_constraints: usize,
+    ) -> Result<usize, ProofError> {
+        let num_constraints = |rc| -> Result<usize, ProofError> {
+            let folding_config = Arc::new(self.folding_config(lang.clone(), rc));
+            let mut cs = MetricCS::<F>::new();
+            C1LEM::<'_, F, C>::blank(folding_config, 0).synthesize(&mut cs)?;
+            Ok(cs.num_constraints())
+        };
+        let base = num_constraints(1)?;
+        if target_constraints < base {
+            return Err(ProofError::ConstraintTargetTooLow {
+                target: target_constraints,
+                min: base,
+            });
+        }
+        // guards against a circuit that doesn't grow with the reduction count
+        let per_reduction = num_constraints(2)?.saturating_sub(base).max(1);
+        Ok(1 + (target_constraints - base) / per_reduction)
+    }
+
+    /// Returns the number of folding steps needed to prove the evaluation of
+    /// `src` with the reduction count `rc`, without proving it. The source is
+    /// evaluated in the empty environment, as in `prove_source`, and in NIVC,
+    /// each coprocessor call takes a step of its own.
+    pub fn count_steps<F: LurkField, C: Coprocessor<F>>(
+        &self,
+        store: &Store<F>,
+        src: &str,
+        rc: usize,
+        lang: &Lang<F, C>,
+    ) -> Result<usize, ProofError> {
+        let expr = store
+            .read_with_default_state(src)
+            .map_err(|e| ReductionError::Misc(e.to_string()))?;
+        let frames = C1LEM::<'_, F, C>::build_frames(
+            expr,
+            store.intern_empty_env(),
+            store,
+            usize::MAX,
+            &self.eval_config(lang),
+        )?;
+        Ok(C1LEM::<'_, F, C>::count_steps(&frames, rc))
+    }
+}
+
+/// The steps, public input and public output cached for an evaluation
+type CachedSteps<'a, F, C> = (Vec<C1LEM<'a, F, C>>, Vec<F>, Vec<F>);
+
+/// A cache of the steps built for evaluations, so that proving the same input
+/// repeatedly, e.g. a popular query, only evaluates it once. Entries are keyed
+/// by the hashes of the input expression and environment, along with the limit
+/// of iterations, and are used by `Prover::evaluate_and_prove_cached`.
+///
+/// The cache holds up to a fixed number of evaluations, evicting the least
+/// recently used one to make room for another. The cached steps are shared
+/// with the callers, and only copied by the provers, which consume them.
+///
+/// The cached steps are only valid for the `Lang`, reduction count and folding
+/// mode they were built for, and point into the store they were evaluated in.
+/// The whole cache is invalidated when it's used with another one of these.
+/// A `Lang` is identified by its digest, as given by
+/// `supernova::nivc_set_digest`, so an equal `Lang` behind another `Arc` shares
+/// the cached steps.
+pub struct FrameCache<'a, F: CurveCycleEquipped, C: Coprocessor<F>> {
+    inner: Mutex<FrameCacheInner<'a, F, C>>,
+    capacity: usize,
+}
+
+struct FrameCacheInner<'a, F: CurveCycleEquipped, C: Coprocessor<F>> {
+    /// The store, `Lang` digest, reduction count and folding mode of the
+    /// entries
+    config: Option<(&'a Store<F>, F, usize, FoldingMode)>,
+    /// The entries, along with the value of `clock` when they were last used
+    entries: HashMap<(Vec<ZPtr<F>>, usize), (Arc<CachedSteps<'a, F, C>>, u64)>,
+    /// Incremented on each use of an entry
+    clock: u64,
+}
+
+impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> FrameCacheInner<'a, F, C> {
+    fn is_for(
+        &self,
+        store: &Store<F>,
+        lang_digest: F,
+        rc: usize,
+        folding_mode: FoldingMode,
+    ) -> bool {
+        matches!(&self.config, Some((s, l, r, m))
+            if std::ptr::eq(*s, store) && *l == lang_digest && *r == rc && *m == folding_mode)
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> Default for FrameCache<'a, F, C> {
+    fn default() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> FrameCache<'a, F, C> {
+    /// The number of evaluations held by a cache created by `new`
+    pub const DEFAULT_CAPACITY: usize = 32;
+
+    /// Creates an empty cache holding up to `DEFAULT_CAPACITY` evaluations
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty cache holding up to `capacity` evaluations. Nothing is
+    /// cached with a capacity of zero.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(FrameCacheInner {
+                config: None,
+                entries: HashMap::new(),
+                clock: 0,
+            }),
+            capacity,
+        }
+    }
+
+    /// The maximum number of cached evaluations
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of cached evaluations
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    /// Whether no evaluation is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all the cached evaluations
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.config = None;
+    }
+
+    /// Returns the steps, public input and public output of the evaluation of
+    /// `expr` in `env`, from the cache if possible. Otherwise, `expr` is
+    /// evaluated and its steps are cached.
+    ///
+    /// The cache isn't locked during the evaluation, so that concurrent jobs
+    /// can evaluate different inputs.
+    #[allow(clippy::too_many_arguments)]
+    fn steps(
+        &self,
+        store: &'a Store<F>,
+        lang: &Arc<Lang<F, C>>,
+        rc: usize,
+        folding_mode: FoldingMode,
+        expr: Ptr,
+        env: Ptr,
+        limit: usize,
+    ) -> Result<Arc<CachedSteps<'a, F, C>>, ProofError>
+    where
+        <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+        <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    {
+        let key = (vec![store.hash_ptr(&expr), store.hash_ptr(&env)], limit);
+        let lang_digest = nivc_set_digest::<F, C>(1, lang);
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if !inner.is_for(store, lang_digest, rc, folding_mode) {
+                inner.entries.clear();
+                inner.config = Some((store, lang_digest, rc, folding_mode));
+            } else {
+                let now = inner.tick();
+                if let Some((cached, last_used)) = inner.entries.get_mut(&key) {
+                    *last_used = now;
+                    return Ok(cached.clone());
+                }
+            }
+        }
+
+        let frames = C1LEM::<'a, F, C>::build_frames(
+            expr,
+            env,
+            store,
+            limit,
+            &folding_mode.eval_config(lang),
+        )?;
+        store.hydrate_z_cache();
+        let z0 = store.to_scalar_vector(frames[0].input());
+        let zi = store.to_scalar_vector(frames.last().unwrap().output());
+        let folding_config = folding_mode.folding_config(lang.clone(), rc);
+        let steps = C1LEM::<'a, F, C>::from_frames(&frames, store, &folding_config.into());
+        let cached = Arc::new((steps, z0, zi));
+
+        let mut inner = self.inner.lock().unwrap();
+        // the cache may have been used with another configuration meanwhile
+        if self.capacity > 0 && inner.is_for(store, lang_digest, rc, folding_mode) {
+            if inner.entries.len() >= self.capacity && !inner.entries.contains_key(&key) {
+                let least_recently_used = inner
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, (_, last_used))| *last_used)
+                    .map(|(lru_key, _)| lru_key.clone());
+                if let Some(lru_key) = least_recently_used {
+                    inner.entries.remove(&lru_key);
+                }
+            }
+            let now = inner.tick();
+            inner.entries.insert(key, (cached.clone(), now));
+        }
+        Ok(cached)
+    }
 }
 
 /// A trait for a prover that works with a field `F`.
@@ -171,6 +2006,10 @@ pub trait Prover<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> {
     /// Returns a reference to the Prover's Lang.
     fn lang(&self) -> &Arc<Lang<F, C>>;
 
+    /// Returns the thread pool the prover runs its jobs in, if it has one of
+    /// its own. See `RecursiveSNARKTrait::prove_recursively_in`.
+    fn thread_pool(&self) -> Option<&Arc<ThreadPool>>;
+
     /// Generate a proof from a sequence of frames
     fn prove(
         &self,
@@ -190,7 +2029,8 @@ pub trait Prover<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> {
         let steps = C1LEM::<'a, F, C>::from_frames(frames, store, &folding_config.into());
         let num_steps = steps.len();
 
-        let prove_output = Self::RecursiveSnark::prove_recursively(
+        let prove_output = Self::RecursiveSnark::prove_recursively_in(
+            self.thread_pool().map(|pool| &**pool),
             pp,
             &z0,
             steps,
@@ -202,6 +2042,21 @@ pub trait Prover<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> {
         Ok((prove_output, z0, zi, num_steps))
     }
 
+    /// Rebuilds the frames recorded in `trace` and proves them
+    fn prove_from_trace(
+        &self,
+        pp: &Self::PublicParams,
+        trace: &FrameTrace<F>,
+        store: &'a Store<F>,
+    ) -> Result<(Self::RecursiveSnark, Vec<F>, Vec<F>, usize), ProofError> {
+        if trace.is_empty() {
+            return Err(ProofError::NoSteps);
+        }
+        let eval_config = self.folding_mode().eval_config(self.lang());
+        let frames = trace.rebuild_frames(store, &eval_config)?;
+        self.prove(pp, &frames, store)
+    }
+
     /// Evaluate an expression with an environment and then generate the corresponding proof
     fn evaluate_and_prove(
         &self,
@@ -210,12 +2065,153 @@ pub trait Prover<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> {
         env: Ptr,
         store: &'a Store<F>,
         limit: usize,
+    ) -> Result<(Self::RecursiveSnark, Vec<F>, Vec<F>, usize), ProofError> {
+        self.evaluate_and_prove_with_cont(pp, expr, env, store.cont_outermost(), store, limit)
+    }
+
+    /// Like `evaluate_and_prove`, but starting from the continuation `cont`
+    /// instead of the outermost one. `env` can hold predefined bindings, which
+    /// are then part of the public input `z0` of the proof.
+    ///
+    /// Errors with `ProofError::InvalidInitialEnv` or
+    /// `ProofError::InvalidInitialCont` if `env` or `cont` is malformed.
+    fn evaluate_and_prove_with_cont(
+        &self,
+        pp: &Self::PublicParams,
+        expr: Ptr,
+        env: Ptr,
+        cont: Ptr,
+        store: &'a Store<F>,
+        limit: usize,
     ) -> Result<(Self::RecursiveSnark, Vec<F>, Vec<F>, usize), ProofError> {
         let eval_config = self.folding_mode().eval_config(self.lang());
-        let frames = C1LEM::<'a, F, C>::build_frames(expr, env, store, limit, &eval_config)?;
+        let frames =
+            C1LEM::<'a, F, C>::build_frames_with_cont(expr, env, cont, store, limit, &eval_config)?;
         self.prove(pp, &frames, store)
     }
 
+    /// Like `evaluate_and_prove`, but reusing the steps cached in `cache` for
+    /// the same `expr`, `env` and `limit`, if any, instead of evaluating `expr`
+    /// again. Otherwise, the steps built for the proof are cached.
+    fn evaluate_and_prove_cached(
+        &self,
+        pp: &Self::PublicParams,
+        expr: Ptr,
+        env: Ptr,
+        store: &'a Store<F>,
+        limit: usize,
+        cache: &FrameCache<'a, F, C>,
+    ) -> Result<(Self::RecursiveSnark, Vec<F>, Vec<F>, usize), ProofError>
+    where
+        <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+        <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    {
+        let lang = self.lang();
+        let rc = self.reduction_count();
+        let cached = cache.steps(store, lang, rc, *self.folding_mode(), expr, env, limit)?;
+        let (steps, z0, zi) = &*cached;
+        let prove_output = Self::RecursiveSnark::prove_recursively_in(
+            self.thread_pool().map(|pool| &**pool),
+            pp,
+            z0,
+            steps.clone(),
+            store,
+            rc,
+            lang.clone(),
+        )?;
+        Ok((prove_output, z0.clone(), zi.clone(), steps.len()))
+    }
+
+    /// Proves a transition of some state known through its commitment, e.g. a
+    /// batch of updates to a key-value store, by evaluating `(update <comm>)`,
+    /// where `<comm>` is the commitment `prev_state_commitment` and `update` is
+    /// a function taking the previous state and returning the commitment to
+    /// the next one, typically with `hide`. The state commitments are thus
+    /// carried by the expressions of the public input and output.
+    ///
+    /// To read the previous state, `update` opens its commitment, so `store`
+    /// must know its opening. Coprocessors can implement the actual updates,
+    /// e.g. checking Merkle paths.
+    ///
+    /// Returns the proof, the commitment to the new state and the public input
+    /// and output to verify the proof with. Errors with
+    /// `ProofError::InvalidStateTransition` if the evaluation doesn't terminate
+    /// within `limit` reductions with a commitment, before proving anything.
+    fn prove_state_transition(
+        &self,
+        pp: &Self::PublicParams,
+        prev_state_commitment: F,
+        update: Ptr,
+        store: &'a Store<F>,
+        limit: usize,
+    ) -> Result<(Self::RecursiveSnark, F, Vec<F>, Vec<F>), ProofError> {
+        let expr = store.list(vec![update, store.comm(prev_state_commitment)]);
+        let eval_config = self.folding_mode().eval_config(self.lang());
+        let frames = C1LEM::<'a, F, C>::build_frames(
+            expr,
+            store.intern_empty_env(),
+            store,
+            limit,
+            &eval_config,
+        )?;
+        let output = &frames.last().ok_or(ProofError::NoSteps)?.output;
+        if *output[0].tag() != Tag::Expr(ExprTag::Comm) || output[2] != store.cont_terminal() {
+            return Err(ProofError::InvalidStateTransition);
+        }
+        let new_state_commitment = *store.hash_ptr(&output[0]).value();
+        let (proof, z0, zi, _num_steps) = self.prove(pp, &frames, store)?;
+        Ok((proof, new_state_commitment, z0, zi))
+    }
+
+    /// Proves the run of a program known through its commitment on `input`,
+    /// by evaluating `((open <program_commitment>) <input>)` in the empty
+    /// environment. The program is the function committed to, e.g. with
+    /// `(hide <secret> (lambda (x) ...))`, so `store` must know its opening,
+    /// whereas `input` is evaluated as an argument, and must thus be quoted if
+    /// it isn't self-evaluating.
+    ///
+    /// The public input only holds the commitment and the input, as laid out
+    /// by `committed_program_input`, so the proof binds the program without
+    /// revealing it, and is verified with
+    /// `RecursiveSNARKTrait::verify_committed_program`. As with
+    /// `RecursiveSNARKTrait::verify_committed_input`, the program only stays
+    /// hidden if the secret of its commitment is random.
+    fn prove_committed_program(
+        &self,
+        pp: &Self::PublicParams,
+        program_commitment: F,
+        input: Ptr,
+        store: &'a Store<F>,
+        limit: usize,
+    ) -> Result<(Self::RecursiveSnark, Vec<F>, Vec<F>, usize), ProofError> {
+        let expr = committed_program_call(store, program_commitment, input);
+        self.evaluate_and_prove(pp, expr, store.intern_empty_env(), store, limit)
+    }
+
+    /// Proves a session of top-level evaluations, like a REPL transcript, as
+    /// one proof. The items are evaluated in order from `env`, and each
+    /// binding extends the environment the following items are evaluated in,
+    /// so that e.g. a symbol defined by the first item can be used by the
+    /// second one.
+    ///
+    /// The items are expanded into a single program, so their frames fold into
+    /// one proof. Its public input, as laid out by `session_input`, commits to
+    /// `env` and to every item, and its output, as laid out by
+    /// `session_output`, is the pair of the result of the last item and the
+    /// final environment. An item whose evaluation errors ends the session in
+    /// that error.
+    fn prove_session(
+        &self,
+        pp: &Self::PublicParams,
+        store: &'a Store<F>,
+        env: Ptr,
+        items: &[SessionItem],
+        limit: usize,
+    ) -> Result<(Self::RecursiveSnark, Vec<F>, Vec<F>, usize), ProofError> {
+        let expr = session_program(store, items);
+        self.evaluate_and_prove(pp, expr, env, store, limit)
+    }
+
     /// Returns the expected total number of steps for the prover given raw iterations.
     fn expected_num_steps(&self, raw_iterations: usize) -> usize {
         let rc = self.reduction_count();
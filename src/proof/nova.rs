@@ -1,6 +1,6 @@
 use abomonation::Abomonation;
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem};
-use ff::PrimeField;
+use ff::{Field, PrimeField};
 use halo2curves::bn256::Fr as Bn256Scalar;
 use nova::{
     errors::NovaError,
@@ -14,27 +14,54 @@ use nova::{
     CompressedSNARK, ProverKey, R1CSWithArity, RecursiveSNARK, VerifierKey,
 };
 use pasta_curves::pallas;
+use rayon::{prelude::*, ThreadPool};
 use serde::{Deserialize, Serialize};
+use std::{marker::PhantomData, sync::Arc};
+
+#[cfg(not(target_arch = "wasm32"))]
+use abomonation::decode;
+#[cfg(not(target_arch = "wasm32"))]
+use camino::Utf8Path;
+#[cfg(not(target_arch = "wasm32"))]
+use memmap::{MmapMut, MmapOptions};
+#[cfg(not(target_arch = "wasm32"))]
 use std::{
-    marker::PhantomData,
-    sync::{Arc, Mutex},
+    fs::File,
+    io::{self, BufReader, BufWriter},
+    ops::Deref,
 };
 
 use crate::{
-    config::lurk_config,
     coprocessor::Coprocessor,
     error::ProofError,
     eval::lang::Lang,
     field::LurkField,
     lem::store::Store,
-    proof::{supernova::FoldingConfig, FrameLike, Prover},
+    proof::{
+        supernova::{check_lang_digests, FoldingConfig, LangDigestCheck},
+        FrameLike, Provable, Prover,
+    },
 };
 
-use super::{FoldingMode, RecursiveSNARKTrait};
+use super::{
+    check_reduction_count, extend_digest, fold_steps, CompressedStats, FoldingMode, FoldingPath,
+    OnStep, ProofDebugInfo, ProvingSettings, RecursiveSNARKTrait, VerificationAttestation,
+    VerifyOutcome, IO_ARITY,
+};
 
 /// This trait defines most of the requirements for programming generically over the supported Nova curve cycles
 /// (currently Pallas/Vesta and BN254/Grumpkin). It being pegged on the `LurkField` trait encodes that we do
 /// not expect more than one such cycle to be supported at a time for a given field.
+///
+/// ## Testing
+///
+/// There is no smaller field to prove over in tests: an implementation needs a cycle of elliptic curves, each with the
+/// Nova `Engine`, commitment and evaluation engines listed below, and no toy cycle provides them. A smaller field
+/// wouldn't make proving much faster anyway, since its cost is dominated by the number of constraints of the Lurk
+/// circuits, which is the same on every field. The proving path is exercised faster by:
+/// - checking circuits without proving, e.g. with `TestConstraintSystem` or `MetricCS`, as in `proof::tests`;
+/// - proving few steps with a small reduction count, since the public parameters grow with it;
+/// - reusing public parameters across tests through the disk cache of the `public_parameters` module.
 pub trait CurveCycleEquipped: LurkField {
     /// ## Why the next 2 types?
 
@@ -54,6 +81,9 @@ pub trait CurveCycleEquipped: LurkField {
     type E1: Engine<Base = <Self::E2 as Engine>::Scalar, Scalar = Self>;
     /// The  group type for the second curve in the cycle.
     type E2: Engine<Base = <Self::E1 as Engine>::Scalar>;
+
+    /// Human-readable name of the curve cycle, primary curve first
+    const CURVE_NAME: &'static str;
 }
 
 impl CurveCycleEquipped for pallas::Scalar {
@@ -62,6 +92,8 @@ impl CurveCycleEquipped for pallas::Scalar {
 
     type E1 = PallasEngine;
     type E2 = VestaEngine;
+
+    const CURVE_NAME: &'static str = "pallas/vesta";
 }
 // The impl CurveCycleEquipped for vesta::Scalar is academically possible, but voluntarily omitted to avoid confusion.
 
@@ -71,6 +103,8 @@ impl CurveCycleEquipped for Bn256Scalar {
 
     type E1 = Bn256Engine;
     type E2 = GrumpkinEngine;
+
+    const CURVE_NAME: &'static str = "bn256/grumpkin";
 }
 // The impl CurveCycleEquipped for grumpkin::Scalar is academically possible, but voluntarily omitted to avoid confusion.
 
@@ -119,6 +153,7 @@ where
     pp: NovaPublicParams<F, SC>,
     pk: ProverKey<E1<F>, E2<F>, SC, C2<F>, SS1<F>, SS2<F>>,
     vk: VerifierKey<E1<F>, E2<F>, SC, C2<F>, SS1<F>, SS2<F>>,
+    rc: usize,
 }
 
 impl<F: CurveCycleEquipped, SC: StepCircuit<F>> Abomonation for PublicParams<F, SC>
@@ -126,6 +161,7 @@ where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
     <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
 {
+    // `rc` is stored inline, so it has nothing to entomb
     unsafe fn entomb<W: std::io::Write>(&self, bytes: &mut W) -> std::io::Result<()> {
         self.pp.entomb(bytes)?;
         self.pk.entomb(bytes)?;
@@ -148,6 +184,326 @@ where
     }
 }
 
+impl<F: CurveCycleEquipped, SC: StepCircuit<F>> PublicParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// return the digest
+    pub fn digest(&self) -> F {
+        self.pp.digest()
+    }
+
+    /// Triggers the computations Nova leaves for first use, so they aren't paid
+    /// for by the first proof. For now, that's the digest of the parameters,
+    /// which every proof and verification starts by absorbing and which is
+    /// computed by hashing all of the circuit shapes. Parameters loaded from the
+    /// disk cache need this unless the digest was computed before they were
+    /// written. See also `MmapPublicParams::warm_up`.
+    #[inline]
+    pub fn warm_up(&self) {
+        self.digest();
+    }
+
+    /// Returns a digest binding `extra` to the digest of the parameters, e.g. to
+    /// account for the version or the configuration of a deployment.
+    pub fn extended_digest(&self, extra: &[u8]) -> F {
+        extend_digest(self.digest(), extra)
+    }
+
+    /// The folding mode of the proofs verifiable with these parameters
+    #[inline]
+    pub fn folding_mode(&self) -> FoldingMode {
+        FoldingMode::IVC
+    }
+
+    /// The name of the curve cycle the parameters are built for, which is also
+    /// recorded in the metadata of the disk cache
+    #[inline]
+    pub fn curve_name(&self) -> &'static str {
+        F::CURVE_NAME
+    }
+
+    /// The reduction count the parameters were generated for
+    #[inline]
+    pub fn reduction_count(&self) -> usize {
+        self.rc
+    }
+
+    /// Whether proofs made with the reduction count `rc` can be proven and
+    /// verified with these parameters.
+    ///
+    /// This only holds for the reduction count the parameters were generated
+    /// for. The shape of the circuit folding Lurk reductions depends on it, and
+    /// the digest of the parameters, which every folding step commits to, binds
+    /// that shape. Proofs across reduction counts can't be verified, so proofs
+    /// of short computations have to be padded to the reduction count of the
+    /// verifier instead.
+    #[inline]
+    pub fn supports_rc(&self, rc: usize) -> bool {
+        rc == self.rc
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<F: CurveCycleEquipped, SC: StepCircuit<F>> PublicParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// Memory-maps a file of abomonated public parameters, such as the ones
+    /// written by the public parameters disk cache. Pages are only faulted in
+    /// when they're accessed, which avoids reading multi-gigabyte files eagerly.
+    ///
+    /// # Safety
+    ///
+    /// The mapping is private (copy-on-write), but the file must not be
+    /// modified or truncated by this or any other process while the returned
+    /// [`MmapPublicParams`] is alive. Doing so is undefined behavior.
+    pub unsafe fn mmap_from(path: &Utf8Path) -> Result<MmapPublicParams<F, SC>, ProofError> {
+        let file = File::open(path)?;
+        let mut mmap = MmapOptions::new().map_copy(&file)?;
+        let decoded = matches!(
+            decode::<Self>(&mut mmap),
+            Some((_, remaining)) if remaining.is_empty()
+        );
+        if !decoded {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("failed to decode public params from {path}"),
+            )
+            .into());
+        }
+        Ok(MmapPublicParams {
+            mmap,
+            _p: PhantomData,
+        })
+    }
+}
+
+/// Public parameters backed by a memory-mapped file. See [`PublicParams::mmap_from`].
+#[cfg(not(target_arch = "wasm32"))]
+pub struct MmapPublicParams<F, SC> {
+    /// The decoded bytes, which start with the `PublicParams` value
+    mmap: MmapMut,
+    _p: PhantomData<(F, SC)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<F: CurveCycleEquipped, SC: StepCircuit<F>> MmapPublicParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// Faults in every page of the mapping, then warms up the parameters with
+    /// `PublicParams::warm_up`. Otherwise the pages are read from disk as the
+    /// first proof touches them, commitment keys included, which makes it
+    /// noticeably slower than the following ones.
+    pub fn warm_up(&self) {
+        const PAGE_SIZE: usize = 4096;
+        let checksum = self
+            .mmap
+            .iter()
+            .step_by(PAGE_SIZE)
+            .fold(0u8, |acc, byte| acc ^ byte);
+        std::hint::black_box(checksum);
+        (**self).warm_up();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<F: CurveCycleEquipped, SC: StepCircuit<F>> Deref for MmapPublicParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    type Target = PublicParams<F, SC>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // Safety: the bytes were successfully decoded in `mmap_from`, which
+        // leaves a valid value at the (page-aligned) start of the mapping
+        unsafe { &*(self.mmap.as_ptr() as *const Self::Target) }
+    }
+}
+
+/// The part of [`PublicParams`] needed to verify compressed proofs, which is
+/// much smaller since it has neither the prover key nor the commitment keys
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct VerifierOnlyParams<F, SC: StepCircuit<F>>
+where
+    F: CurveCycleEquipped,
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    vk: VerifierKey<E1<F>, E2<F>, SC, C2<F>, SS1<F>, SS2<F>>,
+    digest: F,
+}
+
+impl<F: CurveCycleEquipped, SC: StepCircuit<F>> VerifierOnlyParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// The digest of the public parameters these were extracted from
+    #[inline]
+    pub fn digest(&self) -> F {
+        self.digest
+    }
+}
+
+impl<F: CurveCycleEquipped, SC: StepCircuit<F>> PublicParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// Extracts the parameters needed to verify compressed proofs
+    pub fn verifier_only(&self) -> VerifierOnlyParams<F, SC> {
+        VerifierOnlyParams {
+            vk: self.vk.clone(),
+            digest: self.digest(),
+        }
+    }
+}
+
+/// A self-contained file to verify compressed proofs with: the verifier-only
+/// parameters, along with the reduction count and the key of the `Lang` they
+/// were generated for, so auditors can tell what they're verifying
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct VerifierBundle<F, SC: StepCircuit<F>>
+where
+    F: CurveCycleEquipped,
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// The parameters to verify proofs with
+    pub params: VerifierOnlyParams<F, SC>,
+    /// The reduction count of the parameters
+    pub rc: usize,
+    /// The key of the `Lang` of the parameters, as returned by `Lang::key`
+    pub lang_key: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<F: CurveCycleEquipped, SC: StepCircuit<F>> VerifierBundle<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// Writes a bundle to verify proofs generated with `pp` and `lang`
+    pub fn export<C: Coprocessor<F>>(
+        pp: &PublicParams<F, SC>,
+        lang: &Lang<F, C>,
+        path: &Utf8Path,
+    ) -> Result<(), ProofError> {
+        let bundle = Self {
+            params: pp.verifier_only(),
+            rc: pp.reduction_count(),
+            lang_key: lang.key(),
+        };
+        bincode::serialize_into(BufWriter::new(File::create(path)?), &bundle)?;
+        Ok(())
+    }
+
+    /// Reads a bundle written by `VerifierBundle::export`
+    pub fn read(path: &Utf8Path) -> Result<Self, ProofError> {
+        Ok(bincode::deserialize_from(BufReader::new(File::open(
+            path,
+        )?))?)
+    }
+}
+
+/// Verifies the compressed proof in the file at `proof_path` with the verifier
+/// bundle at `bundle_path`. The proof file holds a bincode-serialized
+/// [`ChainedSegment`], i.e. the proof along with its input and output, as
+/// generated by `prove_recursively_chained` without intermediate compression.
+///
+/// Errors with `ProofError::ReductionCountMismatch` if the bundle wasn't
+/// exported for the reduction count `rc`, and with `ProofError::LangKeyMismatch`
+/// if it wasn't exported for `lang`, so a bundle for another setup isn't taken
+/// for the one the caller expects to verify with.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn verify_bundle_file<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
+    bundle_path: &Utf8Path,
+    proof_path: &Utf8Path,
+    rc: usize,
+    lang: &Lang<F, C>,
+) -> Result<bool, ProofError>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    let bundle = VerifierBundle::<F, C1LEM<'a, F, C>>::read(bundle_path)?;
+    check_reduction_count(bundle.rc, rc)?;
+    let lang_key = lang.key();
+    if bundle.lang_key != lang_key {
+        return Err(ProofError::LangKeyMismatch {
+            expected: lang_key,
+            got: bundle.lang_key,
+        });
+    }
+    let segment: super::ChainedSegment<Proof<'a, F, C>, F> =
+        bincode::deserialize_from(BufReader::new(File::open(proof_path)?))?;
+    segment
+        .proof
+        .verify_compressed(&bundle.params, &segment.z0, &segment.zi)
+}
+
+/// A recursive proof in progress, as written by `Proof::prove_with_checkpoints`
+/// and read back by `Proof::resume_from_checkpoint`.
+///
+/// # File format
+/// A checkpoint file holds this struct serialized with `bincode`'s default
+/// options: the digest of the public parameters the proof was folded with,
+/// followed by the recursive `Proof` itself, which records the number of
+/// steps folded so far. Checkpoints are only meant to be read back by the
+/// same version of Lurk, with the same public parameters.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Checkpoint<'a, F: CurveCycleEquipped, C: Coprocessor<F>>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// The digest of the public parameters of the proof
+    pub params_digest: F,
+    /// The recursive proof of the steps folded so far
+    pub proof: Proof<'a, F, C>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> Checkpoint<'a, F, C>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// The number of steps folded in the checkpointed proof
+    pub fn num_steps(&self) -> usize {
+        match &self.proof {
+            Proof::Recursive(_, num_steps, ..) | Proof::Compressed(_, num_steps, ..) => *num_steps,
+        }
+    }
+
+    /// Writes the checkpoint to `path`. The file is written next to `path`
+    /// first and then renamed, so an interruption never leaves a truncated
+    /// checkpoint behind.
+    pub fn write(&self, path: &Utf8Path) -> Result<(), ProofError> {
+        let tmp_path = path.with_extension("tmp");
+        bincode::serialize_into(BufWriter::new(File::create(&tmp_path)?), self)?;
+        std::fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Reads a checkpoint written by `Checkpoint::write`
+    pub fn read(path: &Utf8Path) -> Result<Self, ProofError> {
+        Ok(bincode::deserialize_from(BufReader::new(File::open(
+            path,
+        )?))?)
+    }
+}
+
 /// An enum representing the two types of proofs that can be generated and verified.
 #[derive(Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -161,6 +517,7 @@ where
     Recursive(
         Box<RecursiveSNARK<E1<F>, E2<F>, C1LEM<'a, F, C>, C2<F>>>,
         usize,
+        FoldingMode,
         PhantomData<&'a C>,
     ),
     /// A proof for the final step of a recursive computation along with the number
@@ -168,6 +525,7 @@ where
     Compressed(
         Box<CompressedSNARK<E1<F>, E2<F>, C1LEM<'a, F, C>, C2<F>, SS1<F>, SS2<F>>>,
         usize,
+        FoldingMode,
         PhantomData<&'a C>,
     ),
 }
@@ -188,6 +546,18 @@ pub fn circuit_cache_key<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
 }
 
 /// Generates the public parameters for the Nova proving system.
+///
+/// The commitment keys are sized from the circuit shapes and the `ck_floor`
+/// hints of the compressed SNARKs, and derived deterministically from a fixed
+/// label, so they don't depend on any trusted setup. Parameters generated for
+/// the same `Lang` and reduction count are thus identical across machines, as
+/// attested by their digest. Loading keys from a ceremony output isn't
+/// supported, since Nova's setup doesn't accept externally provided keys.
+///
+/// The setup is a pure computation over the circuit shapes, so it has no
+/// transient failure to retry: it either succeeds or fails the same way on
+/// every attempt, and running out of memory aborts the process rather than
+/// returning an error.
 pub fn public_params<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
     reduction_count: usize,
     lang: Arc<Lang<F, C>>,
@@ -208,7 +578,12 @@ where
         &*commitment_size_hint2,
     );
     let (pk, vk) = CompressedSNARK::setup(&pp).unwrap();
-    PublicParams { pp, pk, vk }
+    PublicParams {
+        pp,
+        pk,
+        vk,
+        rc: reduction_count,
+    }
 }
 
 /// Generates the circuits for the Nova proving system.
@@ -223,6 +598,266 @@ pub fn circuits<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
     )
 }
 
+impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> Proof<'a, F, C>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// Extends a recursive proof with `steps`, which must continue the
+    /// computation from the output of the proof, without folding its steps
+    /// again. The result proves the original steps followed by `steps`, from
+    /// the input of the original proof.
+    ///
+    /// Two finished proofs can't be appended to one another: each accumulates
+    /// its own running instance from its own input, and Nova can't fold two
+    /// running instances together. The steps of the second part must therefore
+    /// be folded on top of the first one.
+    ///
+    /// Errors with `ProofError::AlreadyCompressed` if the proof is compressed,
+    /// with `ProofError::ReductionCountMismatch` if a step doesn't have the
+    /// reduction count of `pp`, with `ProofError::LangDigestMismatch` if a step
+    /// wasn't built for `lang` and with `ProofError::NonContiguousComposition`
+    /// if the first step doesn't start at the output of the proof, before
+    /// folding anything.
+    pub fn append_steps(
+        self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        lang: &Arc<Lang<F, C>>,
+    ) -> Result<Self, ProofError> {
+        let Self::Recursive(mut recursive_snark, num_steps, folding_mode, _) = self else {
+            return Err(ProofError::AlreadyCompressed);
+        };
+        for step in &steps {
+            check_reduction_count(pp.reduction_count(), step.num_frames())?;
+        }
+        check_lang_digests(&steps, lang)?;
+        let Some(first_step) = steps.first() else {
+            return Ok(Self::Recursive(
+                recursive_snark,
+                num_steps,
+                folding_mode,
+                PhantomData,
+            ));
+        };
+        let z0 = first_step
+            .input()
+            .as_ref()
+            .map(|input| store.to_scalar_vector(input));
+        if z0.as_deref() != Some(recursive_snark.outputs().0) {
+            return Err(ProofError::NonContiguousComposition);
+        }
+        let circuit_secondary = TrivialCircuit::default();
+        for step in &steps {
+            recursive_snark.prove_step(&pp.pp, step, &circuit_secondary)?;
+        }
+        Ok(Self::Recursive(
+            recursive_snark,
+            num_steps + steps.len(),
+            folding_mode,
+            PhantomData,
+        ))
+    }
+
+    /// Proves `steps` like `prove_recursively`, writing a [`Checkpoint`] of the
+    /// running proof to `path` after every `every` steps and after the last
+    /// one. If proving is interrupted, `resume_from_checkpoint` continues from
+    /// the last checkpoint written instead of starting over.
+    ///
+    /// Only the first `every` steps benefit from the parallel witness
+    /// generation of `prove_recursively`: the following ones are folded with
+    /// `append_steps`.
+    ///
+    /// Errors with `ProofError::ZeroCheckpointInterval` if `every` is zero.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_checkpoints(
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+        path: &Utf8Path,
+        every: usize,
+    ) -> Result<Self, ProofError> {
+        if every == 0 {
+            return Err(ProofError::ZeroCheckpointInterval);
+        }
+        let params_digest = pp.digest();
+        let mut steps = steps.into_iter();
+        let first_steps = steps.by_ref().take(every).collect();
+        let mut proof =
+            Self::prove_recursively(pp, z0, first_steps, store, reduction_count, lang.clone())?;
+        loop {
+            let checkpoint = Checkpoint {
+                params_digest,
+                proof,
+            };
+            checkpoint.write(path)?;
+            proof = checkpoint.proof;
+            let next_steps: Vec<_> = steps.by_ref().take(every).collect();
+            if next_steps.is_empty() {
+                return Ok(proof);
+            }
+            proof = proof.append_steps(pp, next_steps, store, &lang)?;
+        }
+    }
+
+    /// Reads the checkpoint at `path` and folds `remaining_steps` on top of its
+    /// proof with `append_steps`. `remaining_steps` must start where the
+    /// checkpointed proof stopped, i.e. skip the first `Checkpoint::num_steps`
+    /// steps of the computation.
+    ///
+    /// Errors with `ProofError::CheckpointParamsMismatch` if the checkpoint was
+    /// made with public parameters other than `pp`, since folding on top of it
+    /// would only yield a proof that doesn't verify, and like `append_steps`
+    /// otherwise.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn resume_from_checkpoint(
+        path: &Utf8Path,
+        remaining_steps: Vec<C1LEM<'a, F, C>>,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        lang: &Arc<Lang<F, C>>,
+    ) -> Result<Self, ProofError> {
+        let checkpoint = Checkpoint::read(path)?;
+        if checkpoint.params_digest != pp.digest() {
+            return Err(ProofError::CheckpointParamsMismatch);
+        }
+        checkpoint
+            .proof
+            .append_steps(pp, remaining_steps, store, lang)
+    }
+
+    /// Verifies a compressed proof with verifier-only parameters. Errors with
+    /// `ProofError::NotCompressed` on recursive proofs, which can only be
+    /// verified with the full public parameters, and with
+    /// `ProofError::FoldingModeMismatch` on proofs that weren't folded in IVC.
+    pub fn verify_compressed(
+        &self,
+        vp: &VerifierOnlyParams<F, C1LEM<'a, F, C>>,
+        z0: &[F],
+        zi: &[F],
+    ) -> Result<bool, ProofError> {
+        let Self::Compressed(p, num_steps, folding_mode, _) = self else {
+            return Err(ProofError::NotCompressed);
+        };
+        if z0.len() != IO_ARITY || zi.len() != IO_ARITY {
+            return Err(ProofError::ArityMismatch {
+                expected: IO_ARITY,
+                z0: z0.len(),
+                zi: zi.len(),
+            });
+        }
+        if *folding_mode != FoldingMode::IVC {
+            return Err(ProofError::FoldingModeMismatch {
+                expected: FoldingMode::IVC,
+                got: *folding_mode,
+            });
+        }
+        let z0_secondary = [<E2<F> as Engine>::Scalar::ZERO];
+        // as in `verify_detailed`, the secondary output is checked by Nova itself
+        let (zi_primary, _) = p.verify(&vp.vk, *num_steps, z0, &z0_secondary)?;
+        Ok(zi_primary == zi)
+    }
+
+    /// Verifies a batch of compressed proofs, each along with its input and
+    /// output, returning whether they are all valid. Errors with
+    /// `ProofError::NotCompressed` if a proof of the batch isn't compressed,
+    /// before verifying anything.
+    ///
+    /// The proofs are verified independently, in parallel, and the batch is
+    /// rejected as soon as one of them fails. Their checks aren't combined
+    /// into one: the compressed SNARKs are Spartan proofs over the IPA
+    /// polynomial commitment scheme, which doesn't involve pairings, and
+    /// `nova` doesn't expose the multi-scalar multiplications of its IPA
+    /// checks, which a random linear combination could otherwise merge. The
+    /// batch is thus exactly as sound as verifying each proof on its own, and
+    /// a `false` result doesn't tell which proof failed.
+    pub fn batch_verify_compressed(
+        proofs: &[(&Self, &[F], &[F])],
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+    ) -> Result<bool, ProofError> {
+        if proofs.iter().any(|(proof, ..)| !proof.is_compressed()) {
+            return Err(ProofError::NotCompressed);
+        }
+        proofs
+            .par_iter()
+            .map(|(proof, z0, zi)| proof.verify(pp, z0, zi))
+            .find_any(|result| !matches!(result, Ok(true)))
+            .unwrap_or(Ok(true))
+    }
+
+    /// Returns the sizes of the circuits covered by a compressed proof, read from
+    /// the shapes in `pp`, or `None` if the proof isn't compressed
+    pub fn compressed_stats(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+    ) -> Option<CompressedStats> {
+        match self {
+            Self::Recursive(..) => None,
+            Self::Compressed(..) => {
+                let (primary_constraints, secondary_constraints) = pp.pp.num_constraints();
+                let (primary_variables, secondary_variables) = pp.pp.num_variables();
+                Some(CompressedStats {
+                    primary_constraints,
+                    primary_variables,
+                    secondary_constraints,
+                    secondary_variables,
+                })
+            }
+        }
+    }
+
+    /// Verifies the proof like `verify` and returns the outcome as an
+    /// attestation for the verifier to sign. Proofs that fail to verify,
+    /// including with an error, are attested as invalid.
+    pub fn verify_and_attest(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        z0: &[F],
+        zi: &[F],
+    ) -> Result<VerificationAttestation<F>, ProofError> {
+        Ok(VerificationAttestation {
+            proof_digest: self.digest()?,
+            params_digest: pp.digest(),
+            z0: z0.to_vec(),
+            zi: zi.to_vec(),
+            valid: matches!(self.verify(pp, z0, zi), Ok(true)),
+        })
+    }
+    /// Returns the structural metadata of the proof, see [`ProofDebugInfo`].
+    /// Only recursive proofs carry their output, so compressed ones have no
+    /// output arity.
+    pub fn debug_dump(&self) -> Result<ProofDebugInfo<F>, ProofError> {
+        // serialized once for both its size and its digest
+        let proof = bincode::serialize(self)?;
+        let (snark, num_steps, folding_mode, output_arity) = match self {
+            Self::Recursive(p, num_steps, folding_mode, _) => (
+                bincode::serialize(p)?,
+                *num_steps,
+                *folding_mode,
+                Some(p.outputs().0.len()),
+            ),
+            Self::Compressed(p, num_steps, folding_mode, _) => {
+                (bincode::serialize(p)?, *num_steps, *folding_mode, None)
+            }
+        };
+        Ok(ProofDebugInfo {
+            compressed: self.is_compressed(),
+            folding_mode,
+            num_steps,
+            output_arity,
+            size_bytes: proof.len(),
+            snark_bytes: snark.len(),
+            digest: extend_digest(F::ZERO, &proof),
+            snark_digest: extend_digest(F::ZERO, &snark),
+        })
+    }
+}
+
 impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> RecursiveSNARKTrait<'a, F, C> for Proof<'a, F, C>
 where
     <F as PrimeField>::Repr: Abomonation,
@@ -233,16 +868,45 @@ where
     type ErrorType = NovaError;
 
     #[tracing::instrument(skip_all, name = "nova::prove_recursively")]
-    fn prove_recursively(
+    fn prove_recursively_with_settings(
+        pool: Option<&ThreadPool>,
+        settings: &ProvingSettings,
         pp: &PublicParams<F, C1LEM<'a, F, C>>,
         z0: &[F],
         steps: Vec<C1LEM<'a, F, C>>,
         store: &'a Store<F>,
         reduction_count: usize,
         lang: Arc<Lang<F, C>>,
-    ) -> Result<Self, ProofError> {
-        assert!(!steps.is_empty());
-        assert_eq!(steps[0].arity(), z0.len());
+        on_step: &mut OnStep<'_, 'a, F, C>,
+    ) -> Result<(Self, FoldingPath), ProofError> {
+        if let Some(pool) = pool {
+            if pool.current_thread_index().is_none() {
+                return pool.install(|| {
+                    Self::prove_recursively_with_settings(
+                        Some(pool),
+                        settings,
+                        pp,
+                        z0,
+                        steps,
+                        store,
+                        reduction_count,
+                        lang,
+                        on_step,
+                    )
+                });
+            }
+        }
+        check_reduction_count(pp.reduction_count(), reduction_count)?;
+        check_lang_digests(&steps, &lang)?;
+        let Some(first_step) = steps.first() else {
+            return Err(ProofError::NoSteps);
+        };
+        if first_step.arity() != z0.len() {
+            return Err(ProofError::InputArityMismatch {
+                expected: first_step.arity(),
+                got: z0.len(),
+            });
+        }
         let debug = false;
         let z0_primary = z0;
         let z0_secondary = Self::z0_secondary();
@@ -260,51 +924,15 @@ where
         let mut recursive_snark: Option<RecursiveSNARK<E1<F>, E2<F>, C1LEM<'a, F, C>, C2<F>>> =
             None;
 
-        // the shadowing here is voluntary
-        let recursive_snark = if lurk_config(None, None)
-            .perf
-            .parallelism
-            .recursive_steps
-            .is_parallel()
-        {
-            let cc = steps.into_iter().map(Mutex::new).collect::<Vec<_>>();
-
-            crossbeam::thread::scope(|s| {
-                s.spawn(|_| {
-                    // Skip the very first circuit's witness, so `prove_step` can begin immediately.
-                    // That circuit's witness will not be cached and will just be computed on-demand.
-                    cc.iter().skip(1).for_each(|mf| {
-                        mf.lock()
-                            .unwrap()
-                            .cache_witness(store)
-                            .expect("witness caching failed");
-                    });
-                });
+        tracing::debug!("folding path: {:?}", settings.folding_path);
 
-                for circuit_primary in cc.iter() {
-                    let circuit_primary = circuit_primary.lock().unwrap();
-                    assert_eq!(reduction_count, circuit_primary.frames().unwrap().len());
-
-                    let mut r_snark = recursive_snark.unwrap_or_else(|| {
-                        RecursiveSNARK::new(
-                            &pp.pp,
-                            &circuit_primary,
-                            &circuit_secondary,
-                            z0_primary,
-                            &z0_secondary,
-                        )
-                        .expect("Failed to construct initial recursive snark")
-                    });
-                    r_snark
-                        .prove_step(&pp.pp, &circuit_primary, &circuit_secondary)
-                        .expect("failure to prove Nova step");
-                    recursive_snark = Some(r_snark);
-                }
-                recursive_snark
-            })
-            .unwrap()
-        } else {
-            for circuit_primary in steps.iter() {
+        let folding_path = fold_steps(
+            pool,
+            settings,
+            steps,
+            store,
+            on_step,
+            |_, circuit_primary| {
                 assert_eq!(reduction_count, circuit_primary.frames().unwrap().len());
                 if debug {
                     // For debugging purposes, synthesize the circuit and check that the constraint system is satisfied.
@@ -327,61 +955,144 @@ where
                     assert!(cs.is_satisfied());
                 }
 
-                let mut r_snark = recursive_snark.unwrap_or_else(|| {
-                    RecursiveSNARK::new(
+                // errors are propagated with their `NovaError` as their source
+                let mut r_snark = match recursive_snark.take() {
+                    Some(r_snark) => r_snark,
+                    None => RecursiveSNARK::new(
                         &pp.pp,
                         circuit_primary,
                         &circuit_secondary,
                         z0_primary,
                         &z0_secondary,
-                    )
-                    .expect("Failed to construct initial recursive snark")
-                });
-                r_snark
-                    .prove_step(&pp.pp, circuit_primary, &circuit_secondary)
-                    .expect("failure to prove Nova step");
+                    )?,
+                };
+                r_snark.prove_step(&pp.pp, circuit_primary, &circuit_secondary)?;
                 recursive_snark = Some(r_snark);
-            }
-            recursive_snark
-        };
+                Ok(())
+            },
+        )?;
+
+        let proof = Self::Recursive(
+            Box::new(recursive_snark.ok_or(ProofError::NoSteps)?),
+            num_steps,
+            FoldingMode::IVC,
+            PhantomData,
+        );
+        Ok((proof, folding_path))
+    }
+
+    #[tracing::instrument(skip_all, name = "nova::prove_recursively_lazy")]
+    fn prove_recursively_lazy<I: IntoIterator<Item = Result<C1LEM<'a, F, C>, ProofError>>>(
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        z0: &[F],
+        steps: I,
+        _store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+    ) -> Result<Self, ProofError> {
+        check_reduction_count(pp.reduction_count(), reduction_count)?;
+        let mut lang_check = LangDigestCheck::new(&lang);
+        let z0_secondary = Self::z0_secondary();
+        let (_circuit_primary, circuit_secondary): (
+            C1LEM<'a, F, C>,
+            TrivialCircuit<<E2<F> as Engine>::Scalar>,
+        ) = circuits(reduction_count, lang.clone());
+
+        let mut recursive_snark: Option<RecursiveSNARK<E1<F>, E2<F>, C1LEM<'a, F, C>, C2<F>>> =
+            None;
+        let mut num_steps = 0;
+        for (i, step) in steps.into_iter().enumerate() {
+            let circuit_primary = step?;
+            lang_check.check(i, &circuit_primary)?;
+            let mut r_snark = match recursive_snark.take() {
+                Some(r_snark) => r_snark,
+                None => RecursiveSNARK::new(
+                    &pp.pp,
+                    &circuit_primary,
+                    &circuit_secondary,
+                    z0,
+                    &z0_secondary,
+                )?,
+            };
+            r_snark.prove_step(&pp.pp, &circuit_primary, &circuit_secondary)?;
+            recursive_snark = Some(r_snark);
+            num_steps += 1;
+        }
 
         Ok(Self::Recursive(
-            Box::new(recursive_snark.unwrap()),
+            Box::new(recursive_snark.ok_or(ProofError::NoSteps)?),
             num_steps,
+            FoldingMode::IVC,
             PhantomData,
         ))
     }
 
     fn compress(self, pp: &PublicParams<F, C1LEM<'a, F, C>>) -> Result<Self, ProofError> {
         match self {
-            Self::Recursive(recursive_snark, num_steps, _) => Ok(Self::Compressed(
+            Self::Recursive(recursive_snark, num_steps, folding_mode, _) => Ok(Self::Compressed(
                 Box::new(CompressedSNARK::<_, _, _, _, SS1<F>, SS2<F>>::prove(
                     &pp.pp,
                     &pp.pk,
                     &recursive_snark,
                 )?),
                 num_steps,
+                folding_mode,
                 PhantomData,
             )),
             Self::Compressed(..) => Ok(self),
         }
     }
 
-    fn verify(&self, pp: &Self::PublicParams, z0: &[F], zi: &[F]) -> Result<bool, Self::ErrorType> {
-        let (z0_primary, zi_primary) = (z0, zi);
-        let z0_secondary = Self::z0_secondary();
-        let zi_secondary = &z0_secondary;
+    #[inline]
+    fn is_compressed(&self) -> bool {
+        matches!(self, Self::Compressed(..))
+    }
 
-        let (zi_primary_verified, zi_secondary_verified) = match self {
-            Self::Recursive(p, num_steps, _) => {
-                p.verify(&pp.pp, *num_steps, z0_primary, &z0_secondary)?
-            }
-            Self::Compressed(p, num_steps, _) => {
-                p.verify(&pp.vk, *num_steps, z0_primary, &z0_secondary)?
+    #[inline]
+    fn folding_mode(&self) -> FoldingMode {
+        match self {
+            Self::Recursive(_, _, folding_mode, _) | Self::Compressed(_, _, folding_mode, _) => {
+                *folding_mode
             }
+        }
+    }
+
+    #[inline]
+    fn unverified_output(&self) -> Option<&[F]> {
+        match self {
+            Self::Recursive(p, ..) => Some(p.outputs().0),
+            Self::Compressed(..) => None,
+        }
+    }
+
+    fn verify_detailed(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        zi: &[F],
+    ) -> VerifyOutcome<F, Self::ErrorType> {
+        if self.folding_mode() != pp.folding_mode() {
+            return VerifyOutcome::FoldingModeMismatch {
+                expected: pp.folding_mode(),
+                got: self.folding_mode(),
+            };
+        }
+        // The secondary circuit is trivial, so its IO is the same for all proofs
+        // and can live on the stack instead of being allocated on every call
+        let z0_secondary = [<E2<F> as Engine>::Scalar::ZERO];
+
+        let verified = match self {
+            Self::Recursive(p, num_steps, ..) => p.verify(&pp.pp, *num_steps, z0, &z0_secondary),
+            Self::Compressed(p, num_steps, ..) => p.verify(&pp.vk, *num_steps, z0, &z0_secondary),
         };
 
-        Ok(zi_primary == zi_primary_verified && zi_secondary == &zi_secondary_verified)
+        match verified {
+            // the secondary output of the trivial `C2` is checked by Nova itself
+            Ok((zi_primary_verified, _)) => {
+                VerifyOutcome::from_outputs::<()>(zi, zi_primary_verified, None)
+            }
+            Err(e) => VerifyOutcome::CryptographicFailure(e),
+        }
     }
 }
 
@@ -392,6 +1103,8 @@ pub struct NovaProver<'a, F: CurveCycleEquipped, C: Coprocessor<F>> {
     reduction_count: usize,
     lang: Arc<Lang<F, C>>,
     folding_mode: FoldingMode,
+    /// The thread pool proving jobs run in, instead of the global rayon pool.
+    thread_pool: Option<Arc<ThreadPool>>,
     _phantom: PhantomData<&'a ()>,
 }
 
@@ -403,9 +1116,18 @@ impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> NovaProver<'a, F, C> {
             reduction_count,
             lang,
             folding_mode: FoldingMode::IVC,
+            thread_pool: None,
             _phantom: PhantomData,
         }
     }
+
+    /// Runs the proving jobs in `thread_pool` instead of the global rayon pool,
+    /// capping the threads each job can use when several run concurrently
+    #[inline]
+    pub fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
 }
 
 impl<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> Prover<'a, F, C> for NovaProver<'a, F, C>
@@ -426,6 +1148,11 @@ where
         &self.lang
     }
 
+    #[inline]
+    fn thread_pool(&self) -> Option<&Arc<ThreadPool>> {
+        self.thread_pool.as_ref()
+    }
+
     #[inline]
     fn folding_mode(&self) -> &FoldingMode {
         &self.folding_mode
@@ -1,5 +1,6 @@
 use abomonation::Abomonation;
-use ff::PrimeField;
+use bellpepper::util_cs::{metric_cs::MetricCS, Comparable};
+use ff::{Field, PrimeField};
 use nova::{
     supernova::{
         self,
@@ -13,29 +14,37 @@ use nova::{
         Engine,
     },
 };
-use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::{
+    iter::{IntoParallelIterator, ParallelIterator},
+    ThreadPool,
+};
 use serde::{Deserialize, Serialize};
 use std::{
+    io::{BufWriter, Write},
     marker::PhantomData,
-    ops::Index,
-    sync::{Arc, Mutex},
+    ops::{Deref, Index},
+    sync::Arc,
 };
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     config::lurk_config,
     coprocessor::Coprocessor,
-    error::ProofError,
+    error::{ProofError, ReductionError},
     eval::lang::Lang,
     field::LurkField,
-    lem::store::Store,
+    lem::{pointers::Ptr, store::Store, tag::Tag},
     proof::{
         nova::{CurveCycleEquipped, NovaCircuitShape, E1, E2},
-        Prover, RecursiveSNARKTrait,
+        CompressedStats, Prover, RecursiveSNARKTrait, VerifyOutcome,
     },
+    tag::ContTag,
 };
 
-use super::{nova::C1LEM, FoldingMode};
+use super::{
+    check_reduction_count, extend_digest, fold_steps, nova::C1LEM, FoldingMode, FoldingPath,
+    OnStep, ProofDebugInfo, ProvingSettings, VerificationAttestation,
+};
 
 /// Type alias for a Trivial Test Circuit with G2 scalar field elements.
 pub type C2<F> = TrivialSecondaryCircuit<<E2<F> as Engine>::Scalar>;
@@ -59,6 +68,8 @@ where
     pub pk: ProverKey<E1<F>, E2<F>, SC, C2<F>, SS1<F>, SS2<F>>,
     /// Verifier key for SuperNova
     pub vk: VerifierKey<E1<F>, E2<F>, SC, C2<F>, SS1<F>, SS2<F>>,
+    /// Reduction count of the circuit folding Lurk reductions
+    pub rc: usize,
 }
 
 impl<F: CurveCycleEquipped, SC: SuperStepCircuit<F>> Index<usize> for PublicParams<F, SC>
@@ -84,6 +95,158 @@ where
     pub fn digest(&self) -> F {
         self.pp.digest()
     }
+
+    /// Triggers the computations SuperNova leaves for first use, so they aren't
+    /// paid for by the first proof. For now, that's the digest of the
+    /// parameters, which every proof and verification starts by absorbing. See
+    /// `nova::PublicParams::warm_up`.
+    #[inline]
+    pub fn warm_up(&self) {
+        self.digest();
+    }
+
+    /// Returns a digest binding `extra` to the digest of the parameters, e.g. to
+    /// account for the version or the configuration of a deployment.
+    pub fn extended_digest(&self, extra: &[u8]) -> F {
+        extend_digest(self.digest(), extra)
+    }
+
+    /// The folding mode of the proofs verifiable with these parameters
+    #[inline]
+    pub fn folding_mode(&self) -> FoldingMode {
+        FoldingMode::NIVC
+    }
+
+    /// The name of the curve cycle the parameters are built for, which is also
+    /// recorded in the metadata of the disk cache
+    #[inline]
+    pub fn curve_name(&self) -> &'static str {
+        F::CURVE_NAME
+    }
+
+    /// Whether proofs made with the reduction count `rc` can be proven and
+    /// verified with these parameters, which only holds for `self.rc`. As with
+    /// Nova, the digest of the parameters binds the shape of the Lurk circuit,
+    /// which depends on the reduction count. The coprocessor circuits don't,
+    /// but they're bound by the same digest.
+    #[inline]
+    pub fn supports_rc(&self, rc: usize) -> bool {
+        rc == self.rc
+    }
+
+    /// Whether the parameters were built from the circuits identified by
+    /// `digests`, as returned by `circuit_cache_keys` for the reduction count
+    /// and `Lang` the parameters are expected to be for. A cache can check this
+    /// before trusting parameters loaded from somewhere else.
+    ///
+    /// The cache keys of the circuits are their digests scaled by `self.rc`, so
+    /// this also checks the reduction count.
+    pub fn matches_digests(&self, digests: &CircuitDigests<E1<F>>) -> bool {
+        let rc = F::from(self.rc as u64);
+        let circuit_digests = self.pp.circuit_param_digests();
+        circuit_digests.len() == digests.len()
+            && circuit_digests
+                .iter()
+                .zip(digests.iter())
+                .all(|(circuit_digest, expected)| rc * circuit_digest == *expected)
+    }
+}
+
+impl<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> PublicParams<F, C1LEM<'a, F, C>>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    /// Assembles public parameters for `lang` and the reduction count `rc` from
+    /// auxiliary parameters produced separately, e.g. by a ceremony, and the
+    /// shapes of the primary circuits, deriving fresh prover and verifier keys.
+    /// The auxiliary parameters don't include the primary circuit shapes, which
+    /// `nova` only synthesizes during setup, so they must be provided along
+    /// with them.
+    ///
+    /// Like `public_params_with_max_coprocessors`, errors with
+    /// `ProofError::TooManyCircuits` if `lang` has more than `max_coprocessors`
+    /// coprocessors, e.g. the `max_coprocessors` setting of the Lurk config.
+    /// Errors with `ProofError::CircuitCountMismatch` if there isn't one shape
+    /// per circuit of `lang`, and with `ProofError::AuxParamsMismatch` if the
+    /// digest recorded in `aux` doesn't match the digest of the assembled
+    /// parameters, i.e. if `aux` wasn't generated along with `circuit_shapes`.
+    pub fn from_aux_params(
+        aux: SuperNovaAuxParams<F>,
+        circuit_shapes: Vec<NovaCircuitShape<F>>,
+        rc: usize,
+        lang: &Lang<F, C>,
+        max_coprocessors: usize,
+    ) -> Result<Self, ProofError> {
+        check_coprocessor_count(lang, max_coprocessors)?;
+        lang.validate_circuit_mapping()?;
+        let expected = lang.coprocessor_count() + 1;
+        if circuit_shapes.len() != expected {
+            return Err(ProofError::CircuitCountMismatch {
+                expected,
+                got: circuit_shapes.len(),
+            });
+        }
+        // `nova` only checks the digest of the parts with an assertion, and
+        // doesn't expose the digest computation. The unchecked parameters
+        // report the digest recorded in `aux`, while a deserialized copy of
+        // them, which doesn't carry it, computes the digest of their contents
+        let pp =
+            SuperNovaPublicParams::<F, C1LEM<'a, F, C>>::from_parts_unchecked(circuit_shapes, aux);
+        let recorded = pp.digest();
+        let pp: SuperNovaPublicParams<F, C1LEM<'a, F, C>> =
+            bincode::deserialize(&bincode::serialize(&pp)?)?;
+        if pp.digest() != recorded {
+            return Err(ProofError::AuxParamsMismatch);
+        }
+        let (pk, vk) = CompressedSNARK::setup(&pp)?;
+        Ok(Self { pp, pk, vk, rc })
+    }
+}
+
+/// A cheaply clonable handle to [`PublicParams`], meant to be shared by several
+/// proving or verifying threads without copying the underlying parameters.
+pub struct SharedPublicParams<F: CurveCycleEquipped, SC: SuperStepCircuit<F>>(
+    Arc<PublicParams<F, SC>>,
+)
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation;
+
+impl<F: CurveCycleEquipped, SC: SuperStepCircuit<F>> Clone for SharedPublicParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<F: CurveCycleEquipped, SC: SuperStepCircuit<F>> Deref for SharedPublicParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    type Target = PublicParams<F, SC>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<F: CurveCycleEquipped, SC: SuperStepCircuit<F>> From<PublicParams<F, SC>>
+    for SharedPublicParams<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    #[inline]
+    fn from(pp: PublicParams<F, SC>) -> Self {
+        Self(Arc::new(pp))
+    }
 }
 
 /// Type alias for the Evaluation Engine using G1 group elements.
@@ -96,19 +259,72 @@ pub type EE2<F> = <F as CurveCycleEquipped>::EE2;
 // that SNARK would be found at nova::spartan::ppsnark::RelaxedR1CSSNARK,
 pub type SS1<F> = nova::spartan::batched::BatchedRelaxedR1CSSNARK<E1<F>, EE1<F>>;
 /// Type alias for the Relaxed R1CS Spartan SNARK using G2 group elements, EE2.
+///
+/// Unlike `SS1`, this SNARK isn't batched: the primary side of SuperNova has
+/// one running instance per circuit, which the batched SNARK proves at once,
+/// whereas the secondary side only has the single running instance of its one
+/// circuit, so batching has nothing to amortize. SuperNova's `CompressedSNARK`
+/// also requires a non-batched secondary SNARK, so this choice isn't
+/// configurable. As a consequence, the secondary part of compressed proofs
+/// doesn't grow with the number of coprocessors while the primary one does.
 // NOTE: this is not a SNARK that uses computational commitments,
 // that SNARK would be found at nova::spartan::ppsnark::RelaxedR1CSSNARK,
 pub type SS2<F> = nova::spartan::snark::RelaxedR1CSSNARK<E2<F>, EE2<F>>;
 
+/// Returns the type names of the primary and secondary SNARKs used to
+/// compress SuperNova proofs, i.e. `SS1` and `SS2`
+pub fn compressed_snark_types<F: CurveCycleEquipped>() -> (&'static str, &'static str) {
+    (
+        std::any::type_name::<SS1<F>>(),
+        std::any::type_name::<SS2<F>>(),
+    )
+}
+
 /// Generates the running claim params for the SuperNova proving system.
+///
+/// As with Nova, the commitment keys are derived deterministically and sized
+/// from the circuit shapes and `ck_floor` hints, so the parameters are
+/// reproducible from `lang` and `rc` alone.
+///
+/// Errors with `ProofError::TooManyCircuits` if `lang` has more coprocessors
+/// than allowed by the `max_coprocessors` setting of the Lurk config, see
+/// `public_params_with_max_coprocessors` to set another limit, and with
+/// `ProofError::Lang` if its coprocessors can't be mapped to their circuits.
+///
+/// Proofs only verify against parameters generated from the `Lang` they were
+/// proven with, even if another `Lang` has the same coprocessors plus some
+/// more. SuperNova binds the number of circuits into each augmented circuit
+/// and into the running instance, which carries one accumulator per circuit,
+/// so adding a coprocessor changes the shapes of all circuits along with the
+/// digest of the parameters, including for the circuits a proof never used.
+///
+/// As with Nova, the setup has no transient failure: errors are deterministic
+/// and running out of memory aborts the process, so failures aren't retried.
 pub fn public_params<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
     rc: usize,
     lang: Arc<Lang<F, C>>,
-) -> PublicParams<F, C1LEM<'a, F, C>>
+) -> Result<PublicParams<F, C1LEM<'a, F, C>>, ProofError>
 where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
     <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
 {
+    public_params_with_max_coprocessors(rc, lang, lurk_config(None, None).max_coprocessors)
+}
+
+/// Generates the running claim params like `public_params`, allowing `lang` up
+/// to `max_coprocessors` coprocessors instead of the limit set in the Lurk
+/// config.
+pub fn public_params_with_max_coprocessors<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
+    rc: usize,
+    lang: Arc<Lang<F, C>>,
+    max_coprocessors: usize,
+) -> Result<PublicParams<F, C1LEM<'a, F, C>>, ProofError>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    check_coprocessor_count(&lang, max_coprocessors)?;
+    lang.validate_circuit_mapping()?;
     let folding_config = Arc::new(FoldingConfig::new_nivc(lang, rc));
     let non_uniform_circuit = C1LEM::<'a, F, C>::blank(folding_config, 0);
 
@@ -122,7 +338,20 @@ where
         &*commitment_size_hint2,
     );
     let (pk, vk) = CompressedSNARK::setup(&pp).unwrap();
-    PublicParams { pp, pk, vk }
+    Ok(PublicParams { pp, pk, vk, rc })
+}
+
+/// Checks that `lang` doesn't have more than `max` coprocessors, erroring with
+/// `ProofError::TooManyCircuits` otherwise.
+pub fn check_coprocessor_count<F: LurkField, C: Coprocessor<F>>(
+    lang: &Lang<F, C>,
+    max: usize,
+) -> Result<(), ProofError> {
+    let count = lang.coprocessor_count();
+    if count > max {
+        return Err(ProofError::TooManyCircuits { count, max });
+    }
+    Ok(())
 }
 
 /// An enum representing the two types of proofs that can be generated and verified.
@@ -134,14 +363,141 @@ where
     <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
 {
     /// A proof for the intermediate steps of a recursive computation
-    Recursive(Box<RecursiveSNARK<E1<F>, E2<F>>>),
+    Recursive(Box<RecursiveSNARK<E1<F>, E2<F>>>, ProofMeta<E1<F>>),
     /// A proof for the final step of a recursive computation
     Compressed(
         Box<CompressedSNARK<E1<F>, E2<F>, C1LEM<'a, F, C>, C2<F>, SS1<F>, SS2<F>>>,
+        ProofMeta<E1<F>>,
         PhantomData<&'a C>,
     ),
 }
 
+/// Information about the folded computation, recorded at proving time and
+/// carried along with the SNARK.
+///
+/// Note: this data is *not* bound by the SNARK itself. It's only as trustworthy
+/// as the party that produced the proof. For that reason, it doesn't carry a
+/// hash of the store the proof was produced from either: the data a proof is
+/// about is bound by its public input and output, which commit to the pointers
+/// of the computation, and are checked by `verify`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProofMeta<E: Engine> {
+    /// The program counter of each folded step, in folding order
+    pcs: Vec<usize>,
+    /// The public input the proof was generated against
+    z0: Vec<E::Scalar>,
+    /// The public output of the folded computation
+    zi: Vec<E::Scalar>,
+    /// The folding mode the proof was produced under
+    folding_mode: FoldingMode,
+}
+
+impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> Proof<'a, F, C>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    #[inline]
+    fn meta(&self) -> &ProofMeta<E1<F>> {
+        match self {
+            Self::Recursive(_, meta) | Self::Compressed(_, meta, _) => meta,
+        }
+    }
+
+    /// Returns the program counters of the folded steps, as recorded at proving time
+    ///
+    /// The trace isn't bound by the SNARK, see [`ProofMeta`], so proofs can't be
+    /// verified against an expected trace, nor against a budget of coprocessor
+    /// invocations counted from it. Checking the circuits a computation was
+    /// folded with requires its steps, e.g. to reprove them with
+    /// `RecursiveSNARKTrait::reproduce_and_compare`.
+    #[inline]
+    pub fn program_counters(&self) -> &[usize] {
+        &self.meta().pcs
+    }
+
+    /// Returns the public input `z0` the proof was generated against
+    #[inline]
+    pub fn claimed_input(&self) -> &[F] {
+        &self.meta().z0
+    }
+
+    /// Returns the public output `zi` of the computation the proof was generated for
+    #[inline]
+    pub fn claimed_output(&self) -> &[F] {
+        &self.meta().zi
+    }
+
+    /// Returns the sizes of the circuits covered by a compressed proof, or `None`
+    /// if the proof isn't compressed. The shapes are read from `pp`, which holds
+    /// one primary circuit for the reduction plus one per coprocessor of `lang`.
+    /// Since the compressed SNARK batches all the primary circuits, their sizes
+    /// are summed.
+    pub fn compressed_stats(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        lang: &Lang<F, C>,
+    ) -> Option<CompressedStats> {
+        match self {
+            Self::Recursive(..) => None,
+            Self::Compressed(..) => {
+                let (primary_constraints, primary_variables) = (0..=lang.coprocessor_count())
+                    .map(|i| pp.pp.num_constraints_and_variables(i))
+                    .fold((0, 0), |(cons, vars), (c, v)| (cons + c, vars + v));
+                let (secondary_constraints, secondary_variables) =
+                    pp.pp.num_constraints_and_variables_secondary();
+                Some(CompressedStats {
+                    primary_constraints,
+                    primary_variables,
+                    secondary_constraints,
+                    secondary_variables,
+                })
+            }
+        }
+    }
+
+    /// Verifies the proof like `verify` and returns the outcome as an
+    /// attestation for the verifier to sign. Proofs that fail to verify,
+    /// including with an error, are attested as invalid.
+    pub fn verify_and_attest(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        z0: &[F],
+        zi: &[F],
+    ) -> Result<VerificationAttestation<F>, ProofError> {
+        Ok(VerificationAttestation {
+            proof_digest: self.digest()?,
+            params_digest: pp.digest(),
+            z0: z0.to_vec(),
+            zi: zi.to_vec(),
+            valid: matches!(self.verify(pp, z0, zi), Ok(true)),
+        })
+    }
+    /// Returns the structural metadata of the proof, see [`ProofDebugInfo`].
+    /// The number of steps and the output arity come from the recorded
+    /// program counters and output, see [`ProofMeta`].
+    pub fn debug_dump(&self) -> Result<ProofDebugInfo<F>, ProofError> {
+        // serialized once for both its size and its digest
+        let proof = bincode::serialize(self)?;
+        let snark = match self {
+            Self::Recursive(p, _) => bincode::serialize(p)?,
+            Self::Compressed(p, ..) => bincode::serialize(p)?,
+        };
+        let meta = self.meta();
+        Ok(ProofDebugInfo {
+            compressed: self.is_compressed(),
+            folding_mode: meta.folding_mode,
+            num_steps: meta.pcs.len(),
+            output_arity: Some(meta.zi.len()),
+            size_bytes: proof.len(),
+            snark_bytes: snark.len(),
+            digest: extend_digest(F::ZERO, &proof),
+            snark_digest: extend_digest(F::ZERO, &snark),
+        })
+    }
+}
+
 /// A struct for the Nova prover that operates on field elements of type `F`.
 #[derive(Debug)]
 pub struct SuperNovaProver<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> {
@@ -150,6 +506,10 @@ pub struct SuperNovaProver<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> {
     reduction_count: usize,
     lang: Arc<Lang<F, C>>,
     folding_mode: FoldingMode,
+    /// The thread pool proving jobs run in, instead of the global rayon pool.
+    thread_pool: Option<Arc<ThreadPool>>,
+    /// The stack size of the thread proving sources, if not the calling thread.
+    stack_size: Option<usize>,
     _phantom: PhantomData<&'a ()>,
 }
 
@@ -161,9 +521,65 @@ impl<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a> SuperNovaProver<'a, F, C
             reduction_count,
             lang,
             folding_mode: FoldingMode::NIVC,
+            thread_pool: None,
+            stack_size: None,
             _phantom: PhantomData,
         }
     }
+
+    /// Create a new SuperNovaProver with the `Lang` and the reduction count of
+    /// `config`, the inverse of `folding_config` for NIVC configs.
+    ///
+    /// IVC configs are accepted if their `Lang` has no coprocessors, e.g. as
+    /// picked by `FoldingConfig::auto`, since the single Lurk circuit folds the
+    /// same steps in both modes. The prover still proves in NIVC.
+    ///
+    /// Errors with `ProofError::FoldingModeMismatch` on IVC configs with
+    /// coprocessors, which SuperNova can't prove: their Lurk circuit reduces
+    /// the coprocessors itself. Those are proven with `nova::NovaProver`.
+    pub fn from_folding_config(config: Arc<FoldingConfig<F, C>>) -> Result<Self, ProofError> {
+        match &*config {
+            FoldingConfig::NIVC(lang, rc) => Ok(Self::new(*rc, lang.clone())),
+            FoldingConfig::IVC(lang, rc) if lang.coprocessor_count() == 0 => {
+                Ok(Self::new(*rc, lang.clone()))
+            }
+            FoldingConfig::IVC(..) => Err(ProofError::FoldingModeMismatch {
+                expected: FoldingMode::NIVC,
+                got: FoldingMode::IVC,
+            }),
+        }
+    }
+
+    /// The NIVC folding config of the prover's `Lang` and reduction count, which
+    /// its steps are built with
+    #[inline]
+    pub fn folding_config(&self) -> Arc<FoldingConfig<F, C>> {
+        Arc::new(FoldingConfig::new_nivc(
+            self.lang.clone(),
+            self.reduction_count,
+        ))
+    }
+
+    /// Proves sources on a dedicated thread with a stack of `stack_size` bytes,
+    /// instead of the calling thread. Reading, evaluating and hashing deeply
+    /// nested expressions recurses deeply, which can overflow the stack of the
+    /// calling thread, e.g. the 2 MiB of threads spawned by the standard library.
+    ///
+    /// This applies to `prove_source` and `prove_source_with_env`. Witnesses
+    /// cached in parallel to folding are still generated in the rayon pool.
+    #[inline]
+    pub fn with_stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = Some(stack_size);
+        self
+    }
+
+    /// Runs the proving jobs in `thread_pool` instead of the global rayon pool,
+    /// capping the threads each job can use when several run concurrently
+    #[inline]
+    pub fn with_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
 }
 
 impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> RecursiveSNARKTrait<'a, F, C> for Proof<'a, F, C>
@@ -176,132 +592,221 @@ where
     type ErrorType = SuperNovaError;
 
     #[tracing::instrument(skip_all, name = "supernova::prove_recursively")]
-    fn prove_recursively(
+    fn prove_recursively_with_settings(
+        pool: Option<&ThreadPool>,
+        settings: &ProvingSettings,
         pp: &PublicParams<F, C1LEM<'a, F, C>>,
         z0: &[F],
         steps: Vec<C1LEM<'a, F, C>>,
         store: &'a Store<F>,
-        _reduction_count: usize,
-        _lang: Arc<Lang<F, C>>,
-    ) -> Result<Self, ProofError> {
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+        on_step: &mut OnStep<'_, 'a, F, C>,
+    ) -> Result<(Self, FoldingPath), ProofError> {
+        if let Some(pool) = pool {
+            if pool.current_thread_index().is_none() {
+                return pool.install(|| {
+                    Self::prove_recursively_with_settings(
+                        Some(pool),
+                        settings,
+                        pp,
+                        z0,
+                        steps,
+                        store,
+                        reduction_count,
+                        lang,
+                        on_step,
+                    )
+                });
+            }
+        }
+        check_reduction_count(pp.rc, reduction_count)?;
+        check_lang_digests(&steps, &lang)?;
+        C1LEM::<'a, F, C>::check_pc_consistency(&steps)?;
         let mut recursive_snark_option: Option<RecursiveSNARK<E1<F>, E2<F>>> = None;
+        let pcs = steps.iter().map(|step| step.program_counter()).collect();
+        let zi = steps
+            .last()
+            .and_then(|step| step.output().as_ref())
+            .map(|output| store.to_scalar_vector(output))
+            .ok_or(ProofError::NoSteps)?;
 
         let z0_primary = z0;
         let z0_secondary = Self::z0_secondary();
+        // the secondary circuit is the same for all steps if it's stateless,
+        // otherwise it's built for each step
+        let invariant_secondary =
+            secondary_circuit_is_invariant::<F>().then(|| steps[0].secondary_circuit());
 
-        let mut prove_step = |i: usize, step: &C1LEM<'a, F, C>| {
+        // errors are propagated with their `SuperNovaError` as their source
+        let prove_step = |i: usize, step: &C1LEM<'a, F, C>| -> Result<(), ProofError> {
             info!("prove_recursively, step {i}");
+            let step_secondary;
+            let secondary_circuit = match &invariant_secondary {
+                Some(secondary_circuit) => secondary_circuit,
+                None => {
+                    step_secondary = step.secondary_circuit();
+                    &step_secondary
+                }
+            };
 
-            let secondary_circuit = step.secondary_circuit();
-
-            let mut recursive_snark = recursive_snark_option.clone().unwrap_or_else(|| {
-                info!("RecursiveSnark::new {i}");
-                RecursiveSNARK::new(
-                    &pp.pp,
-                    step,
-                    step,
-                    &secondary_circuit,
-                    z0_primary,
-                    &z0_secondary,
-                )
-                .unwrap()
-            });
+            let mut recursive_snark = match recursive_snark_option.take() {
+                Some(recursive_snark) => recursive_snark,
+                None => {
+                    info!("RecursiveSnark::new {i}");
+                    RecursiveSNARK::new(
+                        &pp.pp,
+                        step,
+                        step,
+                        secondary_circuit,
+                        z0_primary,
+                        &z0_secondary,
+                    )?
+                }
+            };
 
             info!("prove_step {i}");
 
-            recursive_snark
-                .prove_step(&pp.pp, step, &secondary_circuit)
-                .unwrap();
+            recursive_snark.prove_step(&pp.pp, step, secondary_circuit)?;
 
             recursive_snark_option = Some(recursive_snark);
+            Ok(())
         };
 
-        if lurk_config(None, None)
-            .perf
-            .parallelism
-            .recursive_steps
-            .is_parallel()
-        {
-            let cc = steps
-                .into_iter()
-                .map(|mf| (mf.program_counter() == 0, Mutex::new(mf)))
-                .collect::<Vec<_>>();
-
-            crossbeam::thread::scope(|s| {
-                s.spawn(|_| {
-                    // Skip the very first circuit's witness, so `prove_step` can begin immediately.
-                    // That circuit's witness will not be cached and will just be computed on-demand.
-
-                    // There are many MultiFrames with PC = 0, each with several inner frames and heavy internal
-                    // paralellism for witness generation. So we do it like on Nova's pipeline.
-                    cc.iter()
-                        .skip(1)
-                        .filter(|(is_zero_pc, _)| *is_zero_pc)
-                        .for_each(|(_, mf)| {
-                            mf.lock()
-                                .unwrap()
-                                .cache_witness(store)
-                                .expect("witness caching failed");
-                        });
-
-                    // There shouldn't be as many MultiFrames with PC != 0 and they only have one inner frame, each with
-                    // poor internal parallelism for witness generation, so we can generate their witnesses in parallel.
-                    // This is mimicking the behavior we had in the Nova pipeline before #941 so...
-                    // TODO: once we have robust benchmarking for NIVC, we should test whether merging this loop with
-                    // the non-parallel one above (and getting rid of the filters) is better
-                    cc.par_iter()
-                        .skip(1)
-                        .filter(|(is_zero_pc, _)| !*is_zero_pc)
-                        .for_each(|(_, mf)| {
-                            mf.lock()
-                                .unwrap()
-                                .cache_witness(store)
-                                .expect("witness caching failed");
-                        });
-                });
+        tracing::debug!("folding path: {:?}", settings.folding_path);
+        let folding_path = fold_steps(pool, settings, steps, store, on_step, prove_step)?;
+
+        // This probably should be made unnecessary.
+        let proof = Self::Recursive(
+            Box::new(recursive_snark_option.expect("RecursiveSNARK missing")),
+            ProofMeta {
+                pcs,
+                z0: z0.to_vec(),
+                zi,
+                folding_mode: FoldingMode::NIVC,
+            },
+        );
+        Ok((proof, folding_path))
+    }
 
-                for (i, (_, step)) in cc.iter().enumerate() {
-                    prove_step(i, &step.lock().unwrap());
+    #[tracing::instrument(skip_all, name = "supernova::prove_recursively_lazy")]
+    fn prove_recursively_lazy<I: IntoIterator<Item = Result<C1LEM<'a, F, C>, ProofError>>>(
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        z0: &[F],
+        steps: I,
+        store: &'a Store<F>,
+        reduction_count: usize,
+        lang: Arc<Lang<F, C>>,
+    ) -> Result<Self, ProofError> {
+        check_reduction_count(pp.rc, reduction_count)?;
+        let mut lang_check = LangDigestCheck::new(&lang);
+        let z0_secondary = Self::z0_secondary();
+        let mut recursive_snark: Option<RecursiveSNARK<E1<F>, E2<F>>> = None;
+        let mut pcs = vec![];
+        let mut last_output = None;
+        let mut secondary = None;
+        let mut prev_next_pc = None;
+        for (i, step) in steps.into_iter().enumerate() {
+            let step = step?;
+            lang_check.check(i, &step)?;
+            prev_next_pc = Some(step.check_pc_after(i, prev_next_pc)?);
+            info!("prove_recursively_lazy, step {i}");
+            // the secondary circuit is the same for all steps if it's stateless,
+            // otherwise it's built for each step
+            let secondary_circuit: &C2<F> = if secondary_circuit_is_invariant::<F>() {
+                secondary.get_or_insert_with(|| step.secondary_circuit())
+            } else {
+                secondary.insert(step.secondary_circuit())
+            };
+            let mut r_snark = match recursive_snark.take() {
+                Some(r_snark) => r_snark,
+                None => {
+                    RecursiveSNARK::new(&pp.pp, &step, &step, secondary_circuit, z0, &z0_secondary)?
                 }
-            })
-            .unwrap()
-        } else {
-            for (i, step) in steps.iter().enumerate() {
-                prove_step(i, step);
-            }
+            };
+            r_snark.prove_step(&pp.pp, &step, secondary_circuit)?;
+            recursive_snark = Some(r_snark);
+            pcs.push(step.program_counter());
+            last_output = step.output().clone();
         }
 
-        // This probably should be made unnecessary.
-        Ok(Self::Recursive(Box::new(
-            recursive_snark_option.expect("RecursiveSNARK missing"),
-        )))
+        let recursive_snark = recursive_snark.ok_or(ProofError::NoSteps)?;
+        let last_output = last_output.ok_or(ProofError::MissingOutput {
+            step_index: pcs.len() - 1,
+        })?;
+        let zi = store.to_scalar_vector(&last_output);
+        Ok(Self::Recursive(
+            Box::new(recursive_snark),
+            ProofMeta {
+                pcs,
+                z0: z0.to_vec(),
+                zi,
+                folding_mode: FoldingMode::NIVC,
+            },
+        ))
     }
 
     fn compress(self, pp: &PublicParams<F, C1LEM<'a, F, C>>) -> Result<Self, ProofError> {
-        match &self {
-            Self::Recursive(recursive_snark) => Ok(Self::Compressed(
+        match self {
+            Self::Recursive(recursive_snark, meta) => Ok(Self::Compressed(
                 Box::new(CompressedSNARK::<_, _, _, _, SS1<F>, SS2<F>>::prove(
                     &pp.pp,
                     &pp.pk,
-                    recursive_snark,
+                    &recursive_snark,
                 )?),
+                meta,
                 PhantomData,
             )),
             Self::Compressed(..) => Ok(self),
         }
     }
 
-    fn verify(&self, pp: &Self::PublicParams, z0: &[F], zi: &[F]) -> Result<bool, Self::ErrorType> {
-        let (z0_primary, zi_primary) = (z0, zi);
-        let z0_secondary = Self::z0_secondary();
-        let zi_secondary = &z0_secondary;
+    #[inline]
+    fn is_compressed(&self) -> bool {
+        matches!(self, Self::Compressed(..))
+    }
+
+    #[inline]
+    fn folding_mode(&self) -> FoldingMode {
+        self.meta().folding_mode
+    }
+
+    #[inline]
+    fn unverified_output(&self) -> Option<&[F]> {
+        Some(self.claimed_output())
+    }
 
-        let (zi_primary_verified, zi_secondary_verified) = match self {
-            Self::Recursive(p) => p.verify(&pp.pp, z0_primary, &z0_secondary)?,
-            Self::Compressed(p, _) => p.verify(&pp.pp, &pp.vk, z0_primary, &z0_secondary)?,
+    fn verify_detailed(
+        &self,
+        pp: &Self::PublicParams,
+        z0: &[F],
+        zi: &[F],
+    ) -> VerifyOutcome<F, Self::ErrorType> {
+        if self.folding_mode() != pp.folding_mode() {
+            return VerifyOutcome::FoldingModeMismatch {
+                expected: pp.folding_mode(),
+                got: self.folding_mode(),
+            };
+        }
+        if z0 != self.claimed_input() {
+            warn!("verifying against an input different from the one claimed by the proof");
+        }
+        // The secondary circuit is trivial, so its IO is the same for all proofs
+        // and can live on the stack instead of being allocated on every call
+        let z0_secondary = [<E2<F> as Engine>::Scalar::ZERO];
+
+        let verified = match self {
+            Self::Recursive(p, _) => p.verify(&pp.pp, z0, &z0_secondary),
+            Self::Compressed(p, _, _) => p.verify(&pp.pp, &pp.vk, z0, &z0_secondary),
         };
 
-        Ok(zi_primary == zi_primary_verified && zi_secondary == &zi_secondary_verified)
+        match verified {
+            // the secondary output of the trivial `C2` is checked by Nova itself
+            Ok((zi_primary_verified, _)) => {
+                VerifyOutcome::from_outputs::<()>(zi, zi_primary_verified, None)
+            }
+            Err(e) => VerifyOutcome::CryptographicFailure(e),
+        }
     }
 }
 
@@ -323,12 +828,186 @@ where
         &self.lang
     }
 
+    #[inline]
+    fn thread_pool(&self) -> Option<&Arc<ThreadPool>> {
+        self.thread_pool.as_ref()
+    }
+
     #[inline]
     fn folding_mode(&self) -> &FoldingMode {
         &self.folding_mode
     }
 }
 
+impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>> SuperNovaProver<'a, F, C>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
+    /// Reads `src` with the default Lurk state, evaluates it in the empty
+    /// environment and proves the evaluation. Returns the proof along with the
+    /// expression the source evaluated to.
+    ///
+    /// Evaluation is bounded by `max_reductions`, erroring with
+    /// `ProofError::EvaluationLimitExceeded` if `src` doesn't terminate within
+    /// that many reductions, in which case nothing is proven. With `None`,
+    /// evaluation isn't bounded, so `src` must be known to terminate.
+    pub fn prove_source(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        src: &str,
+        max_reductions: Option<usize>,
+    ) -> Result<(Proof<'a, F, C>, Ptr), ProofError> {
+        let env = store.intern_empty_env();
+        let cont = store.cont_outermost();
+        self.prove_source_with_env(pp, store, src, env, cont, max_reductions)
+    }
+
+    /// Like `prove_source`, but evaluates `src` in the environment `env` and
+    /// from the continuation `cont`, so the source can refer to predefined
+    /// bindings. Errors with `ProofError::InvalidInitialEnv` or
+    /// `ProofError::InvalidInitialCont` if `env` or `cont` is malformed.
+    pub fn prove_source_with_env(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        src: &str,
+        env: Ptr,
+        cont: Ptr,
+        max_reductions: Option<usize>,
+    ) -> Result<(Proof<'a, F, C>, Ptr), ProofError> {
+        let prove = || self.prove_source_on_this_thread(pp, store, src, env, cont, max_reductions);
+        match self.stack_size {
+            None => prove(),
+            Some(stack_size) => std::thread::scope(|scope| {
+                std::thread::Builder::new()
+                    .name("lurk-prove-source".into())
+                    .stack_size(stack_size)
+                    .spawn_scoped(scope, prove)?
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            }),
+        }
+    }
+
+    fn prove_source_on_this_thread(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        src: &str,
+        env: Ptr,
+        cont: Ptr,
+        max_reductions: Option<usize>,
+    ) -> Result<(Proof<'a, F, C>, Ptr), ProofError> {
+        let expr = store
+            .read_with_default_state(src)
+            .map_err(|e| ReductionError::Misc(e.to_string()))?;
+        let eval_config = self.folding_mode().eval_config(self.lang());
+        let frames = C1LEM::<'a, F, C>::build_frames_with_cont(
+            expr,
+            env,
+            cont,
+            store,
+            max_reductions.unwrap_or(usize::MAX),
+            &eval_config,
+        )?;
+        // there are no frames only if evaluation wasn't allowed any reduction
+        let last_output = &frames
+            .last()
+            .ok_or(ProofError::EvaluationLimitExceeded { limit: 0 })?
+            .output;
+        if let Some(limit) = max_reductions {
+            if !matches!(
+                last_output[2].tag(),
+                Tag::Cont(ContTag::Terminal | ContTag::Error)
+            ) {
+                return Err(ProofError::EvaluationLimitExceeded { limit });
+            }
+        }
+        let output = last_output[0];
+        let (proof, ..) = self.prove(pp, &frames, store)?;
+        Ok((proof, output))
+    }
+
+    /// Proves `steps` from `z0` and writes the recursive proof to `w` as soon as
+    /// folding finishes, serialized with `bincode` as `bincode::deserialize_from`
+    /// reads it back. The proof is serialized straight into `w`, through a
+    /// buffer of its own, so its bytes are never held in memory all at once.
+    ///
+    /// Nothing is written before folding finishes, since the proof doesn't
+    /// exist until then; nor is anything written if proving fails. The proof
+    /// carries its input and output, as given by `Proof::claimed_input` and
+    /// `Proof::claimed_output`, so readers can verify it without `steps`.
+    pub fn prove_to_writer<W: Write>(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        z0: &[F],
+        steps: Vec<C1LEM<'a, F, C>>,
+        store: &'a Store<F>,
+        w: W,
+    ) -> Result<(), ProofError> {
+        let proof = Proof::prove_recursively_in(
+            self.thread_pool.as_deref(),
+            pp,
+            z0,
+            steps,
+            store,
+            self.reduction_count,
+            self.lang.clone(),
+        )?;
+        let mut w = BufWriter::new(w);
+        bincode::serialize_into(&mut w, &proof)?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Proves several independent computations, each given by its initial state
+    /// `z0` and its steps, reusing the same public parameters for all of them.
+    ///
+    /// Up to `max_parallel_jobs` jobs are proven concurrently, in the thread
+    /// pool of the prover if any, as done by
+    /// `RecursiveSNARKTrait::prove_recursively_in`. With `1`, or `0`, the jobs
+    /// are proven one after the other, which suits jobs that keep the threads
+    /// busy on their own: each of the concurrent jobs has a thread caching its
+    /// witnesses, so too many of them could starve the pool. Many small jobs
+    /// are proven faster concurrently. The results follow the order of `jobs`.
+    pub fn prove_many(
+        &self,
+        pp: &PublicParams<F, C1LEM<'a, F, C>>,
+        jobs: Vec<(Vec<F>, Vec<C1LEM<'a, F, C>>)>,
+        store: &'a Store<F>,
+        max_parallel_jobs: usize,
+    ) -> Vec<Result<Proof<'a, F, C>, ProofError>> {
+        let pool = self.thread_pool.as_deref();
+        let prove_job = |(z0, steps): (Vec<F>, Vec<C1LEM<'a, F, C>>)| {
+            Proof::prove_recursively_in(
+                pool,
+                pp,
+                &z0,
+                steps,
+                store,
+                self.reduction_count,
+                self.lang.clone(),
+            )
+        };
+        if max_parallel_jobs <= 1 {
+            return jobs.into_iter().map(prove_job).collect();
+        }
+        let mut results = Vec::with_capacity(jobs.len());
+        let mut jobs = jobs.into_iter().peekable();
+        while jobs.peek().is_some() {
+            let batch: Vec<_> = jobs.by_ref().take(max_parallel_jobs).collect();
+            let prove_batch = || batch.into_par_iter().map(prove_job).collect::<Vec<_>>();
+            results.extend(match pool {
+                Some(pool) => pool.install(prove_batch),
+                None => prove_batch(),
+            });
+        }
+        results
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Folding configuration specifies the `Lang`, the reduction count and the
 /// folding mode for a proving setup.
@@ -358,6 +1037,33 @@ impl<F: LurkField, C: Coprocessor<F>> FoldingConfig<F, C> {
         Self::NIVC(lang, reduction_count)
     }
 
+    /// Create a config for `lang` in the folding mode that suits it: IVC if it
+    /// has no coprocessors and NIVC otherwise. NIVC only pays off when steps
+    /// can be folded by the smaller circuits of coprocessors, so without any
+    /// it's pure overhead over IVC. `new_ivc` and `new_nivc` force a mode.
+    ///
+    /// Each prover is tied to one mode, `nova::NovaProver` to IVC and
+    /// `SuperNovaProver` to NIVC, so the mode of the config picks the prover and
+    /// the public parameters to use, see `folding_mode`. Code tied to
+    /// `SuperNovaProver` can still take the configs of this function through
+    /// `SuperNovaProver::from_folding_config`, which proves the IVC ones in
+    /// NIVC over the single Lurk circuit.
+    pub fn auto(lang: Arc<Lang<F, C>>, reduction_count: usize) -> Self {
+        if lang.coprocessor_count() == 0 {
+            Self::new_ivc(lang, reduction_count)
+        } else {
+            Self::new_nivc(lang, reduction_count)
+        }
+    }
+
+    /// Return the folding mode of this `FoldingConfig`.
+    pub fn folding_mode(&self) -> FoldingMode {
+        match self {
+            Self::IVC(..) => FoldingMode::IVC,
+            Self::NIVC(..) => FoldingMode::NIVC,
+        }
+    }
+
     /// Return the total number of NIVC circuits potentially required when folding
     /// programs described by this `FoldingConfig`.
     pub fn num_circuits(&self) -> usize {
@@ -387,6 +1093,11 @@ impl<F: LurkField, C: Coprocessor<F>> FoldingConfig<F, C> {
 ///
 /// Note: For now, we use ad-hoc circuit cache keys.
 /// See: [crate::public_parameters::instance]
+///
+/// The digests of the blank circuits don't depend on `rc`, so they're computed
+/// once per `Lang` and cached in it. Setting up the parameters of a `Lang`
+/// requires the keys of all its circuits several times over, for the auxiliary
+/// parameters and for each circuit's parameters.
 pub fn circuit_cache_key<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
     rc: usize,
     lang: Arc<Lang<F, C>>,
@@ -396,11 +1107,104 @@ where
     <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
     <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
 {
-    let folding_config = Arc::new(FoldingConfig::new_nivc(lang, 2));
+    let digests = lang.circuit_digests_with(|| blank_circuit_digests::<'a, F, C>(&lang));
+    F::from(rc as u64) * digests[circuit_index]
+}
+
+/// Computes the digests of the blank circuits of `lang`, synthesizing each one
+/// of them exactly once
+fn blank_circuit_digests<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
+    lang: &Arc<Lang<F, C>>,
+) -> Vec<F>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), 2));
+    let circuit = C1LEM::<'a, F, C>::blank(folding_config, 0);
+    let num_circuits = circuit.num_circuits();
+    (0..num_circuits)
+        .map(|circuit_index| {
+            let circuit = circuit.primary_circuit(circuit_index);
+            supernova::circuit_digest::<F::E1, F::E2, _>(&circuit, num_circuits)
+        })
+        .collect()
+}
+
+/// The shape of one of the NIVC circuits, as recorded by `circuit_shape_snapshot`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CircuitShape<F: LurkField> {
+    /// Number of constraints of the circuit
+    pub num_constraints: usize,
+    /// Number of auxiliary variables of the circuit
+    pub num_variables: usize,
+    /// The digest of the circuit's R1CS shape, as computed by `nova`
+    pub digest: F,
+}
+
+/// A stable description of the shapes of all the NIVC circuits of a `Lang` for
+/// a reduction count, meant for golden-file tests: a code change altering the
+/// circuits, and hence invalidating the cached public parameters, changes the
+/// snapshot. Its `Display` gives one line per circuit, to diff snapshots with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ShapeSnapshot<F: LurkField> {
+    /// The reduction count the circuits were built for
+    pub reduction_count: usize,
+    /// The shapes of the circuits, by circuit index: the Lurk step circuit
+    /// first, followed by the coprocessor circuits
+    pub circuits: Vec<CircuitShape<F>>,
+}
+
+impl<F: LurkField> std::fmt::Display for ShapeSnapshot<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "reduction count: {}", self.reduction_count)?;
+        for (circuit_index, shape) in self.circuits.iter().enumerate() {
+            writeln!(
+                f,
+                "circuit {circuit_index}: {} constraints, {} variables, digest {}",
+                shape.num_constraints,
+                shape.num_variables,
+                shape.digest.hex_digits()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes the shapes of the NIVC circuits of `lang` for the reduction count
+/// `rc`, synthesizing each blank circuit once, without setting up any public
+/// parameters. See `ShapeSnapshot`.
+pub fn circuit_shape_snapshot<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
+    rc: usize,
+    lang: &Arc<Lang<F, C>>,
+) -> ShapeSnapshot<F>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
     let circuit = C1LEM::<'a, F, C>::blank(folding_config, 0);
     let num_circuits = circuit.num_circuits();
-    let circuit = circuit.primary_circuit(circuit_index);
-    F::from(rc as u64) * supernova::circuit_digest::<F::E1, F::E2, _>(&circuit, num_circuits)
+    let circuits = (0..num_circuits)
+        .map(|circuit_index| {
+            let circuit = circuit.primary_circuit(circuit_index);
+            let digest = supernova::circuit_digest::<F::E1, F::E2, _>(&circuit, num_circuits);
+            let mut cs = MetricCS::<F>::new();
+            bellpepper_core::Circuit::synthesize(circuit, &mut cs)
+                .expect("failed to synthesize blank");
+            CircuitShape {
+                num_constraints: cs.num_constraints(),
+                num_variables: cs.aux().len(),
+                digest,
+            }
+        })
+        .collect();
+    ShapeSnapshot {
+        reduction_count: rc,
+        circuits,
+    }
 }
 
 /// Collects all the cache keys of supernova instance. We need all of them to compute
@@ -419,3 +1223,87 @@ where
         .collect();
     CircuitDigests::new(digests)
 }
+
+/// A single digest identifying the whole set of NIVC circuits of `lang` for the
+/// reduction count `rc`, hashing the keys returned by `circuit_cache_keys`. It's
+/// the key under which the `AuxParams` of the set are cached.
+pub fn nivc_set_digest<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
+    rc: usize,
+    lang: &Arc<Lang<F, C>>,
+) -> F
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    circuit_cache_keys::<F, C>(rc, lang).digest()
+}
+
+/// Whether the secondary circuit of `MultiFrame` is the same for all steps, so
+/// the provers can build it once per proof. That's the case as long as `C2` is
+/// a trivial circuit, which is zero-sized since it holds no state. Provers fall
+/// back to building it for each step otherwise.
+pub(crate) const fn secondary_circuit_is_invariant<F: CurveCycleEquipped>() -> bool {
+    std::mem::size_of::<C2<F>>() == 0
+}
+
+/// Checks that all `steps` were built for `lang`, comparing the digests of
+/// their `Lang`s, as given by `nivc_set_digest`, unless they share `lang`
+/// itself. Folding steps built for different `Lang`s produces invalid proofs.
+///
+/// Errors with `ProofError::LangDigestMismatch` at the first step built for
+/// another `Lang`.
+pub(crate) fn check_lang_digests<'a, F: CurveCycleEquipped, C: Coprocessor<F> + 'a>(
+    steps: &[C1LEM<'a, F, C>],
+    lang: &Arc<Lang<F, C>>,
+) -> Result<(), ProofError>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    let mut check = LangDigestCheck::new(lang);
+    for (step_index, step) in steps.iter().enumerate() {
+        check.check(step_index, step)?;
+    }
+    Ok(())
+}
+
+/// The check of `check_lang_digests`, one step at a time, for the provers that
+/// get their steps lazily. The digest of `lang` is computed at most once.
+pub(crate) struct LangDigestCheck<'l, F: CurveCycleEquipped, C: Coprocessor<F>> {
+    lang: &'l Arc<Lang<F, C>>,
+    expected: Option<F>,
+}
+
+impl<'l, F: CurveCycleEquipped, C: Coprocessor<F>> LangDigestCheck<'l, F, C>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    #[inline]
+    pub(crate) fn new(lang: &'l Arc<Lang<F, C>>) -> Self {
+        Self {
+            lang,
+            expected: None,
+        }
+    }
+
+    /// Checks that `step`, the one at `step_index`, was built for `lang`
+    pub(crate) fn check(
+        &mut self,
+        step_index: usize,
+        step: &C1LEM<'_, F, C>,
+    ) -> Result<(), ProofError> {
+        let step_lang = step.get_lang();
+        if Arc::ptr_eq(step_lang, self.lang) {
+            return Ok(());
+        }
+        let lang = self.lang;
+        let expected = *self
+            .expected
+            .get_or_insert_with(|| nivc_set_digest::<F, C>(1, lang));
+        if nivc_set_digest::<F, C>(1, step_lang) != expected {
+            return Err(ProofError::LangDigestMismatch { step_index });
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,34 @@
+use abomonation::Abomonation;
+use camino::Utf8Path;
 use expect_test::expect;
+use ff::PrimeField;
+use halo2curves::bn256::Fr as Bn;
+use nova::traits::Engine;
 use pasta_curves::pallas::Scalar as Fr;
 use std::{cell::RefCell, rc::Rc, sync::Arc};
+use tempfile::Builder;
 
 use crate::{
-    eval::lang::{Coproc, Lang},
+    error::ProofError,
+    eval::lang::{Coproc, DummyCoprocessor, Lang},
+    field::LurkField,
     lem::{
+        eval::{evaluate_simple, EvalConfig},
         store::{intern_ptrs, Store},
         tag::Tag,
     },
     num::Num,
+    proof::{
+        available_memory_bytes, committed_input, committed_program_input,
+        nova::{
+            public_params, verify_bundle_file, Checkpoint, CurveCycleEquipped, NovaProver, Proof,
+            VerifierBundle, C1LEM, E1, E2,
+        },
+        session_input, session_output,
+        supernova::FoldingConfig,
+        ChainVerifier, ChainedSegment, Disclosed, FoldingMode, FrameCache, ProofDebugInfo, Prover,
+        RecursiveSNARKTrait, SessionItem, VerifyOptions, IO_ARITY,
+    },
     state::user_sym,
     state::State,
     tag::{ExprTag, Op, Op1, Op2},
@@ -4371,3 +4391,969 @@ fn test_letrec_sequencing() {
         &None,
     );
 }
+
+#[test]
+#[ignore]
+fn test_prove_chained_compression() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let rc = 1;
+    let expr = s.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+    let frames = C1LEM::<'_, Fr, Coproc<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        100,
+        &EvalConfig::new_ivc(&lang),
+    )
+    .unwrap();
+    let folding_config = Arc::new(FoldingConfig::new_ivc(lang.clone(), rc));
+    let steps = C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames, s, &folding_config);
+    let num_steps = steps.len();
+    assert!(num_steps > 2);
+
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+
+    let pp = public_params(rc, lang.clone());
+    let segments =
+        Proof::prove_recursively_chained(&pp, &z0, steps, s, rc, lang.clone(), Some(2)).unwrap();
+    assert_eq!(segments.len(), (num_steps + 1) / 2);
+    assert!(Proof::verify_chain(&segments, &pp, &z0, &zi).unwrap());
+
+    // every prefix of segments of the chain verifies up to its last output
+    for k in 1..=segments.len() {
+        let zi_at_k = &segments[k - 1].zi;
+        assert!(Proof::verify_chain_segments(&segments, &pp, &z0, zi_at_k, k).unwrap());
+    }
+    assert!(!Proof::verify_chain_segments(&segments, &pp, &z0, &zi, 0).unwrap());
+    assert!(!Proof::verify_chain_segments(&segments, &pp, &z0, &zi, segments.len() + 1).unwrap());
+    if segments.len() > 1 {
+        assert!(!Proof::verify_chain_segments(&segments, &pp, &z0, &zi, 1).unwrap());
+    }
+
+    // dropping the first segment breaks the chain at its start
+    assert!(!Proof::verify_chain(&segments[1..], &pp, &z0, &zi).unwrap());
+    // dropping the last segment breaks the chain at its end
+    let last = segments.len() - 1;
+    assert!(!Proof::verify_chain(&segments[..last], &pp, &z0, &zi).unwrap());
+
+    // blank steps have no output to continue the chain from
+    let blank = C1LEM::<'_, Fr, Coproc<Fr>>::blank(folding_config, 0);
+    assert!(matches!(
+        Proof::prove_recursively_chained(&pp, &z0, vec![blank], s, rc, lang.clone(), Some(2)),
+        Err(ProofError::MissingOutput { step_index: 0 })
+    ));
+
+    // segments can be verified one at a time, out of order ones being rejected
+    let mut verifier = ChainVerifier::<'_, '_, _, _, Proof<'_, Fr, Coproc<Fr>>>::new(&pp, &z0);
+    if segments.len() > 1 {
+        assert!(matches!(
+            verifier.push(&segments[1]),
+            Err(ProofError::ChunkInputMismatch { index: 0 })
+        ));
+    }
+    for (i, segment) in segments.iter().enumerate() {
+        verifier.push(segment).unwrap();
+        assert_eq!(verifier.num_chunks(), i + 1);
+        assert_eq!(verifier.current_output(), segment.zi);
+    }
+    assert_eq!(verifier.finalize(), zi);
+}
+
+#[test]
+#[ignore]
+fn test_reduction_count_mismatch() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let pp = public_params(1, lang.clone());
+    assert_eq!(pp.reduction_count(), 1);
+    assert!(pp.supports_rc(1));
+    assert!(!pp.supports_rc(2));
+
+    let expr = s.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(2, lang);
+    assert!(matches!(
+        prover.evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000),
+        Err(ProofError::ReductionCountMismatch {
+            params_rc: 1,
+            rc: 2
+        })
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_append_steps() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let rc = 1;
+    let expr = s.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+    let frames = C1LEM::<'_, Fr, Coproc<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        100,
+        &EvalConfig::new_ivc(&lang),
+    )
+    .unwrap();
+    let folding_config = Arc::new(FoldingConfig::new_ivc(lang.clone(), rc));
+    let mut steps = C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames, s, &folding_config);
+    assert!(steps.len() > 2);
+
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+
+    let pp = public_params(rc, lang.clone());
+    let second_half = steps.split_off(steps.len() / 2);
+    let proof = Proof::prove_recursively(&pp, &z0, steps, s, rc, lang.clone()).unwrap();
+    let proof = proof.append_steps(&pp, second_half, s, &lang).unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+    let compressed = proof.compress(&pp).unwrap();
+    assert!(compressed.verify(&pp, &z0, &zi).unwrap());
+    assert!(matches!(
+        compressed.append_steps(&pp, vec![], s, &lang),
+        Err(ProofError::AlreadyCompressed)
+    ));
+
+    // appending the first step again doesn't continue from the output
+    let first_step = || C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames[..1], s, &folding_config);
+    let proof = Proof::prove_recursively(&pp, &z0, first_step(), s, rc, lang.clone()).unwrap();
+    assert!(matches!(
+        proof.append_steps(&pp, first_step(), s, &lang),
+        Err(ProofError::NonContiguousComposition)
+    ));
+
+    // steps of another reduction count or for another `Lang` are rejected
+    // before anything is folded
+    let proof = Proof::prove_recursively(&pp, &z0, first_step(), s, rc, lang.clone()).unwrap();
+    let rc2_config = Arc::new(FoldingConfig::new_ivc(lang.clone(), 2));
+    let rc2_steps = C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames[1..], s, &rc2_config);
+    assert!(matches!(
+        proof.append_steps(&pp, rc2_steps, s, &lang),
+        Err(ProofError::ReductionCountMismatch {
+            params_rc: 1,
+            rc: 2
+        })
+    ));
+    let mut other = Lang::<Fr, Coproc<Fr>>::new();
+    other.add_coprocessor(user_sym("dummy"), Coproc::Dummy(DummyCoprocessor::new()));
+    let proof = Proof::prove_recursively(&pp, &z0, first_step(), s, rc, lang.clone()).unwrap();
+    let other_config = Arc::new(FoldingConfig::new_ivc(Arc::new(other), rc));
+    let other_steps = C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames[1..], s, &other_config);
+    assert!(matches!(
+        proof.append_steps(&pp, other_steps, s, &lang),
+        Err(ProofError::LangDigestMismatch { step_index: 0 })
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_prove_recursively_invalid_steps() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let rc = 1;
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let frames = C1LEM::<'_, Fr, Coproc<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        100,
+        &EvalConfig::new_ivc(&lang),
+    )
+    .unwrap();
+    let folding_config = Arc::new(FoldingConfig::new_ivc(lang.clone(), rc));
+    let steps = C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames, s, &folding_config);
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let pp = public_params(rc, lang.clone());
+
+    // both are reported as errors, as SuperNova does, instead of panicking
+    assert!(matches!(
+        Proof::prove_recursively(&pp, &z0, vec![], s, rc, lang.clone()),
+        Err(ProofError::NoSteps)
+    ));
+    assert!(matches!(
+        Proof::prove_recursively(&pp, &z0[..4], steps, s, rc, lang),
+        Err(ProofError::InputArityMismatch {
+            expected: 6,
+            got: 4
+        })
+    ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_available_memory_bytes() {
+    assert!(available_memory_bytes().is_some_and(|bytes| bytes > 0));
+}
+
+#[test]
+#[ignore]
+fn test_checkpoints() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let rc = 1;
+    let expr = s.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+    let frames = C1LEM::<'_, Fr, Coproc<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        100,
+        &EvalConfig::new_ivc(&lang),
+    )
+    .unwrap();
+    let folding_config = Arc::new(FoldingConfig::new_ivc(lang.clone(), rc));
+    let steps = || C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames, s, &folding_config);
+    let num_steps = steps().len();
+    assert!(num_steps > 3);
+
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+
+    let pp = public_params(rc, lang.clone());
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let path = Utf8Path::from_path(tmp_dir.path())
+        .unwrap()
+        .join("checkpoint");
+
+    let proof =
+        Proof::prove_with_checkpoints(&pp, &z0, steps(), s, rc, lang.clone(), &path, 2).unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+    let checkpoint = Checkpoint::<'_, Fr, Coproc<Fr>>::read(&path).unwrap();
+    assert_eq!(checkpoint.num_steps(), num_steps);
+    assert_eq!(checkpoint.params_digest, pp.digest());
+    assert!(matches!(
+        Proof::prove_with_checkpoints(&pp, &z0, steps(), s, rc, lang.clone(), &path, 0),
+        Err(ProofError::ZeroCheckpointInterval)
+    ));
+
+    // interrupted after the first checkpoint, then resumed
+    let mut first_steps = steps();
+    let remaining_steps = first_steps.split_off(3);
+    Proof::prove_with_checkpoints(&pp, &z0, first_steps, s, rc, lang.clone(), &path, 2).unwrap();
+    let checkpoint = Checkpoint::<'_, Fr, Coproc<Fr>>::read(&path).unwrap();
+    assert_eq!(checkpoint.num_steps(), 3);
+    let proof = Proof::resume_from_checkpoint(&path, remaining_steps, &pp, s, &lang).unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+
+    // a checkpoint made with other parameters isn't resumed
+    let checkpoint = Checkpoint {
+        params_digest: checkpoint.params_digest + Fr::from(1),
+        proof: checkpoint.proof,
+    };
+    checkpoint.write(&path).unwrap();
+    let mut remaining_steps = steps();
+    let remaining_steps = remaining_steps.split_off(3);
+    assert!(matches!(
+        Proof::resume_from_checkpoint(&path, remaining_steps, &pp, s, &lang),
+        Err(ProofError::CheckpointParamsMismatch)
+    ));
+}
+
+/// The parallel path caches witnesses in a thread of its own while folding, so
+/// the steps are folded with or without their witness depending on the
+/// schedule. The proof must be the same as the one folded sequentially, whatever
+/// the number of threads.
+#[test]
+#[ignore]
+fn test_parallel_folding_is_deterministic() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let rc = 1;
+    let expr = s
+        .read_with_default_state("(let ((f (lambda (x) (* x x)))) (+ (f 2) (f 3)))")
+        .unwrap();
+    let frames = C1LEM::<'_, Fr, Coproc<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_ivc(&lang),
+    )
+    .unwrap();
+    let folding_config = Arc::new(FoldingConfig::new_ivc(lang.clone(), rc));
+    let steps = C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames, s, &folding_config);
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+
+    let pp = public_params(rc, lang.clone());
+    let sequential =
+        Proof::prove_recursively_borrowed(&pp, &z0, &steps, s, rc, lang.clone()).unwrap();
+    let expected = bincode::serialize(&sequential).unwrap();
+    for num_threads in [1, 2, 8] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            let parallel = pool.install(|| {
+                Proof::prove_recursively(&pp, &z0, steps.clone(), s, rc, lang.clone()).unwrap()
+            });
+            assert_eq!(bincode::serialize(&parallel).unwrap(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_frame_cache() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let expr = s.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+    let env = s.intern_empty_env();
+    let cache = FrameCache::with_capacity(2);
+    let steps_of = |lang: &Arc<_>, rc, expr| {
+        cache
+            .steps(s, lang, rc, FoldingMode::IVC, expr, env, 1000)
+            .unwrap()
+    };
+
+    let cached1 = steps_of(&lang, 1, expr);
+    assert_eq!(cache.len(), 1);
+    // hits share the cached steps
+    assert!(Arc::ptr_eq(&cached1, &steps_of(&lang, 1, expr)));
+    assert_eq!(cache.len(), 1);
+
+    // another input gets an entry of its own, up to the capacity, beyond which
+    // the least recently used entry is evicted
+    let other = s.read_with_default_state("(+ 1 2)").unwrap();
+    let third = s.read_with_default_state("(* 2 3)").unwrap();
+    let cached_other = steps_of(&lang, 1, other);
+    assert_eq!(cache.len(), 2);
+    steps_of(&lang, 1, expr);
+    steps_of(&lang, 1, third);
+    assert_eq!(cache.len(), 2);
+    assert!(Arc::ptr_eq(&cached1, &steps_of(&lang, 1, expr)));
+    let cached_other = {
+        let reevaluated = steps_of(&lang, 1, other);
+        assert!(!Arc::ptr_eq(&cached_other, &reevaluated));
+        reevaluated
+    };
+
+    // an equal `Lang` behind another `Arc` shares the cache, while changing the
+    // reduction count invalidates it
+    assert!(Arc::ptr_eq(
+        &cached_other,
+        &steps_of(&Arc::new(Lang::new()), 1, other)
+    ));
+    let cached3 = steps_of(&lang, 2, expr);
+    assert_eq!(cache.len(), 1);
+    assert!(cached3.0.len() < cached1.0.len());
+    assert_eq!((&cached3.1, &cached3.2), (&cached1.1, &cached1.2));
+
+    cache.clear();
+    assert!(cache.is_empty());
+
+    // nothing is cached without capacity
+    let cache = FrameCache::with_capacity(0);
+    cache
+        .steps(s, &lang, 1, FoldingMode::IVC, expr, env, 1000)
+        .unwrap();
+    assert!(cache.is_empty());
+}
+
+#[test]
+#[ignore]
+fn test_prove_in_thread_pool() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let rc = 1;
+    let expr = s.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+    let frames = C1LEM::<'_, Fr, Coproc<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_ivc(&lang),
+    )
+    .unwrap();
+    let pp = public_params(rc, lang.clone());
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let prover = NovaProver::new(rc, lang.clone()).with_thread_pool(Arc::new(pool));
+    let (proof, z0, zi, _) = prover.prove(&pp, &frames, s).unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+
+    // the pool only changes where the work runs, not the proof
+    let (expected, ..) = NovaProver::new(rc, lang).prove(&pp, &frames, s).unwrap();
+    assert_eq!(
+        bincode::serialize(&proof).unwrap(),
+        bincode::serialize(&expected).unwrap()
+    );
+}
+
+#[test]
+#[ignore]
+fn test_prove_recursively_borrowed() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let rc = 1;
+    let expr = s.read_with_default_state("(+ 1 (+ 2 3))").unwrap();
+    let frames = C1LEM::<'_, Fr, Coproc<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        100,
+        &EvalConfig::new_ivc(&lang),
+    )
+    .unwrap();
+    let folding_config = Arc::new(FoldingConfig::new_ivc(lang.clone(), rc));
+    let steps = C1LEM::<'_, Fr, Coproc<Fr>>::from_frames(&frames, s, &folding_config);
+
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+
+    let pp = public_params(rc, lang.clone());
+    let proof = Proof::prove_recursively_borrowed(&pp, &z0, &steps, s, rc, lang.clone()).unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+
+    // the steps are still there to be proven again
+    let proof = Proof::prove_recursively(&pp, &z0, steps, s, rc, lang).unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+
+    let recursive_size = proof.size_bytes().unwrap();
+    let proof = proof.compress_if_larger_than(&pp, recursive_size).unwrap();
+    assert!(!proof.is_compressed());
+    let compressed = proof
+        .compress_if_larger_than(&pp, recursive_size - 1)
+        .unwrap();
+    assert!(compressed.is_compressed());
+    assert!(compressed.size_bytes().unwrap() < recursive_size);
+    assert!(compressed.verify(&pp, &z0, &zi).unwrap());
+    assert!(matches!(
+        compressed.compress_strict(&pp),
+        Err(ProofError::AlreadyCompressed)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_verify_committed_input() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let hidden = s
+        .read_with_default_state("(hide 123 (lambda () (+ 1 2)))")
+        .unwrap();
+    let (output, ..) = evaluate_simple::<Fr, Coproc<Fr>>(None, hidden, s, 100).unwrap();
+    let comm = output[0];
+    let commitment = *s.hash_ptr(&comm).value();
+
+    let expr = s.list(vec![s.list(vec![s.intern_lurk_symbol("open"), comm])]);
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+    assert_eq!(z0, committed_input(s, commitment));
+    assert_eq!(zi[..2], s.to_scalar_vector(&[s.num_u64(3)]));
+
+    assert!(proof.verify_committed_input(&pp, commitment, &zi).unwrap());
+    let other_commitment = commitment + Fr::from(1);
+    assert!(!matches!(
+        proof.verify_committed_input(&pp, other_commitment, &zi),
+        Ok(true)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_prove_committed_program() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let hidden = s
+        .read_with_default_state("(hide 123 (lambda (x) (+ x 1)))")
+        .unwrap();
+    let (output, ..) = evaluate_simple::<Fr, Coproc<Fr>>(None, hidden, s, 100).unwrap();
+    let program_commitment = *s.hash_ptr(&output[0]).value();
+
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let input = s.num_u64(2);
+    let (proof, z0, zi, _num_steps) = prover
+        .prove_committed_program(&pp, program_commitment, input, s, 1000)
+        .unwrap();
+    assert_eq!(z0, committed_program_input(s, program_commitment, input));
+    assert_eq!(zi[..2], s.to_scalar_vector(&[s.num_u64(3)]));
+
+    // the verifier only knows the commitment and the input
+    let verifier_store = &Store::<Fr>::default();
+    let verifier_input = verifier_store.num_u64(2);
+    assert!(proof
+        .verify_committed_program(&pp, program_commitment, verifier_input, verifier_store, &zi)
+        .unwrap());
+    assert!(!matches!(
+        proof.verify_committed_program(
+            &pp,
+            program_commitment,
+            verifier_store.num_u64(1),
+            verifier_store,
+            &zi
+        ),
+        Ok(true)
+    ));
+    assert!(!matches!(
+        proof.verify_committed_program(
+            &pp,
+            program_commitment + Fr::from(1),
+            verifier_input,
+            verifier_store,
+            &zi
+        ),
+        Ok(true)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_verify_disclosed_output() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let expr = s
+        .read_with_default_state("(list (+ 1 2) (hide 123 (* 6 7)))")
+        .unwrap();
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+
+    let hidden = s.hide(Fr::from(123), s.num_u64(42));
+    let commitment = *s.hash_ptr(&hidden).value();
+    let disclosed = [
+        Disclosed::Clear(s.num_u64(3)),
+        Disclosed::Hidden(commitment),
+    ];
+    assert!(proof
+        .verify_disclosed_output(&pp, &z0, &zi, s, &disclosed)
+        .unwrap());
+
+    // a wrong cleartext or commitment doesn't match the output
+    let wrong_clear = [
+        Disclosed::Clear(s.num_u64(4)),
+        Disclosed::Hidden(commitment),
+    ];
+    assert!(!proof
+        .verify_disclosed_output(&pp, &z0, &zi, s, &wrong_clear)
+        .unwrap());
+    let wrong_hidden = [
+        Disclosed::Clear(s.num_u64(3)),
+        Disclosed::Hidden(commitment + Fr::from(1)),
+    ];
+    assert!(!proof
+        .verify_disclosed_output(&pp, &z0, &zi, s, &wrong_hidden)
+        .unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_verify_and_attest() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+
+    let attestation = proof.verify_and_attest(&pp, &z0, &zi).unwrap();
+    assert!(attestation.valid);
+    assert_eq!(attestation.proof_digest, proof.digest().unwrap());
+    assert_eq!(attestation.params_digest, pp.digest());
+    assert_eq!(
+        attestation.to_bytes(),
+        proof.verify_and_attest(&pp, &z0, &zi).unwrap().to_bytes()
+    );
+
+    let mut wrong_zi = zi.clone();
+    wrong_zi[1] += Fr::from(1);
+    let attestation = proof.verify_and_attest(&pp, &z0, &wrong_zi).unwrap();
+    assert!(!attestation.valid);
+    assert_eq!(*attestation.to_bytes().last().unwrap(), 0);
+}
+
+#[test]
+#[ignore]
+fn test_prove_state_transition() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+
+    let state = s.hide(Fr::from(7), s.num_u64(1));
+    let prev_state_commitment = *s.hash_ptr(&state).value();
+    let increment = s
+        .read_with_default_state("(lambda (state) (hide 7 (+ (open state) 1)))")
+        .unwrap();
+    let (proof, new_state_commitment, z0, zi) = prover
+        .prove_state_transition(&pp, prev_state_commitment, increment, s, 1000)
+        .unwrap();
+    let expected = s.hide(Fr::from(7), s.num_u64(2));
+    assert_eq!(new_state_commitment, *s.hash_ptr(&expected).value());
+    assert_eq!(zi[1], new_state_commitment);
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+
+    // the verifier only needs to know the new commitment
+    assert!(proof
+        .verify_output_commitment(&pp, &z0, new_state_commitment)
+        .unwrap());
+    assert!(!proof
+        .verify_output_commitment(&pp, &z0, prev_state_commitment)
+        .unwrap());
+    let compressed = proof.compress(&pp).unwrap();
+    assert!(compressed
+        .verify_output_commitment(&pp, &z0, new_state_commitment)
+        .unwrap());
+    assert!(!compressed
+        .verify_output_commitment(&pp, &z0, prev_state_commitment)
+        .unwrap());
+
+    let forget = s.read_with_default_state("(lambda (state) 1)").unwrap();
+    assert!(matches!(
+        prover.prove_state_transition(&pp, prev_state_commitment, forget, s, 1000),
+        Err(ProofError::InvalidStateTransition)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_verify_with_options() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+    let mut other = zi.clone();
+    other[1] += Fr::from(1);
+
+    assert_eq!(
+        proof.verify_and_extract(&pp, &z0).unwrap(),
+        Some(zi.clone())
+    );
+    let expecting = |zi: &[Fr]| VerifyOptions {
+        expected_output: Some(zi.to_vec()),
+    };
+    assert_eq!(
+        proof
+            .verify_with_options(&pp, &z0, &expecting(&zi))
+            .unwrap(),
+        Some(zi.clone())
+    );
+    assert_eq!(
+        proof
+            .verify_with_options(&pp, &z0, &expecting(&other))
+            .unwrap(),
+        None
+    );
+    assert!(matches!(
+        proof.verify_with_options(&pp, &z0, &expecting(&zi[..2])),
+        Err(ProofError::ArityMismatch { .. })
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_verify_hex() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+    let to_hex = |z: &[Fr]| z.iter().map(|x| x.hex_digits()).collect::<Vec<_>>();
+    let (z0_hex, zi_hex) = (to_hex(&z0), to_hex(&zi));
+    let as_strs = |hex: &[String]| hex.iter().map(String::as_str).collect::<Vec<_>>();
+
+    assert!(proof
+        .verify_hex(&pp, &as_strs(&z0_hex), &as_strs(&zi_hex))
+        .unwrap());
+    let mut bad_zi = as_strs(&zi_hex);
+    bad_zi[1] = "not hex";
+    assert!(matches!(
+        proof.verify_hex(&pp, &as_strs(&z0_hex), &bad_zi),
+        Err(ProofError::InvalidFieldElement(digits)) if digits == "not hex"
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_verify_output_in() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+    let mut other = zi.clone();
+    other[1] += Fr::from(1);
+
+    assert!(proof
+        .verify_output_in(&pp, &z0, &[other.clone(), zi.clone()])
+        .unwrap());
+    assert!(proof
+        .verify_output_in(&pp, &z0, &[zi, other.clone()])
+        .unwrap());
+    assert!(!proof.verify_output_in(&pp, &z0, &[other]).unwrap());
+    assert!(!proof.verify_output_in(&pp, &z0, &[]).unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_batch_verify_compressed() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let prove = |source| {
+        let expr = s.read_with_default_state(source).unwrap();
+        let (proof, z0, zi, _num_steps) = prover
+            .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+            .unwrap();
+        (proof, z0, zi)
+    };
+    let (proof1, z0_1, zi_1) = prove("(+ 1 2)");
+    let (proof2, z0_2, zi_2) = prove("(* 2 3)");
+    let compressed1 = proof1.compress(&pp).unwrap();
+    let compressed2 = proof2.compress(&pp).unwrap();
+
+    assert!(Proof::batch_verify_compressed(
+        &[
+            (&compressed1, &z0_1[..], &zi_1[..]),
+            (&compressed2, &z0_2[..], &zi_2[..])
+        ],
+        &pp
+    )
+    .unwrap());
+    // one wrong output rejects the whole batch
+    assert!(!Proof::batch_verify_compressed(
+        &[
+            (&compressed1, &z0_1[..], &zi_1[..]),
+            (&compressed2, &z0_2[..], &zi_1[..])
+        ],
+        &pp
+    )
+    .unwrap());
+
+    let (recursive, z0, zi) = prove("(+ 1 2)");
+    assert!(matches!(
+        Proof::batch_verify_compressed(
+            &[
+                (&compressed1, &z0_1[..], &zi_1[..]),
+                (&recursive, &z0[..], &zi[..])
+            ],
+            &pp
+        ),
+        Err(ProofError::NotCompressed)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_verify_bundle_file() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang.clone());
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    let bundle_path = tmp_dir.join("bundle");
+    let proof_path = tmp_dir.join("proof");
+    VerifierBundle::export(&pp, &lang, &bundle_path).unwrap();
+
+    let bundle = VerifierBundle::<Fr, C1LEM<'_, Fr, Coproc<Fr>>>::read(&bundle_path).unwrap();
+    assert_eq!(bundle.rc, 1);
+    assert_eq!(bundle.lang_key, lang.key());
+    assert_eq!(bundle.params.digest(), pp.digest());
+
+    let write_proof = |proof: &Proof<'_, Fr, Coproc<Fr>>, zi: &[Fr]| {
+        let segment = ChainedSegment {
+            proof,
+            z0: z0.clone(),
+            zi: zi.to_vec(),
+        };
+        std::fs::write(&proof_path, bincode::serialize(&segment).unwrap()).unwrap();
+    };
+
+    // recursive proofs need the full public parameters
+    write_proof(&proof, &zi);
+    assert!(matches!(
+        verify_bundle_file(&bundle_path, &proof_path, 1, &lang),
+        Err(ProofError::NotCompressed)
+    ));
+
+    let compressed = proof.compress(&pp).unwrap();
+    write_proof(&compressed, &zi);
+    assert!(verify_bundle_file(&bundle_path, &proof_path, 1, &lang).unwrap());
+
+    write_proof(&compressed, &z0);
+    assert!(!verify_bundle_file(&bundle_path, &proof_path, 1, &lang).unwrap());
+
+    // the bundle must be for the expected reduction count and `Lang`
+    assert!(matches!(
+        verify_bundle_file(&bundle_path, &proof_path, 2, &lang),
+        Err(ProofError::ReductionCountMismatch {
+            params_rc: 1,
+            rc: 2
+        })
+    ));
+    let mut other = Lang::<Fr, Coproc<Fr>>::new();
+    other.add_coprocessor(user_sym("dummy"), Coproc::Dummy(DummyCoprocessor::new()));
+    assert!(matches!(
+        verify_bundle_file(&bundle_path, &proof_path, 1, &other),
+        Err(ProofError::LangKeyMismatch { .. })
+    ));
+}
+
+fn test_serde_roundtrip_aux<F: CurveCycleEquipped>()
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    let s = &Store::<F>::default();
+    let lang = Arc::new(Lang::<F, Coproc<F>>::new());
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let prover = NovaProver::<'_, F, Coproc<F>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+
+    let roundtrip = |proof: &Proof<'_, F, Coproc<F>>| {
+        let bytes = bincode::serialize(proof).unwrap();
+        let deserialized: Proof<'_, F, Coproc<F>> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bytes, bincode::serialize(&deserialized).unwrap());
+        assert!(deserialized.verify(&pp, &z0, &zi).unwrap());
+    };
+
+    roundtrip(&proof);
+    let compressed = proof.compress(&pp).unwrap();
+    assert!(matches!(compressed, Proof::Compressed(..)));
+    roundtrip(&compressed);
+}
+
+#[test]
+#[ignore]
+fn test_serde_roundtrip_pallas() {
+    test_serde_roundtrip_aux::<Fr>()
+}
+
+#[test]
+#[ignore]
+fn test_serde_roundtrip_bn256() {
+    test_serde_roundtrip_aux::<Bn>()
+}
+
+#[test]
+#[ignore]
+fn test_prove_session() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let expr = |src| s.read_with_default_state(src).unwrap();
+    let empty = s.intern_empty_env();
+    let x = s.intern_user_symbol("x");
+    // the binding of the first item is visible to the second one
+    let items = [
+        SessionItem::Def(x, expr("(+ 2 3)")),
+        SessionItem::Eval(expr("(* x 2)")),
+    ];
+
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let (proof, z0, zi, _num_steps) = prover.prove_session(&pp, s, empty, &items, 1000).unwrap();
+    assert_eq!(z0, session_input(s, empty, &items));
+    let final_env = s.push_binding(x, s.num_u64(5), empty);
+    assert_eq!(zi[..2], session_output(s, s.num_u64(10), final_env));
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+
+    // the public input commits to the items and to the initial environment
+    let other_items = [SessionItem::Def(x, expr("(+ 2 4)")), items[1]];
+    assert_ne!(z0, session_input(s, empty, &other_items));
+    assert_ne!(z0, session_input(s, final_env, &items));
+}
+
+#[test]
+#[ignore]
+fn test_debug_dump() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let (proof, _z0, _zi, num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+
+    let info = proof.debug_dump().unwrap();
+    assert!(!info.compressed);
+    assert_eq!(info.folding_mode, FoldingMode::IVC);
+    assert_eq!(info.num_steps, num_steps);
+    assert_eq!(info.output_arity, Some(IO_ARITY));
+    assert_eq!(info.size_bytes, proof.size_bytes().unwrap());
+    assert!(info.snark_bytes < info.size_bytes);
+    assert_eq!(info.digest, proof.digest().unwrap());
+    let bytes = bincode::serialize(&info).unwrap();
+    assert_eq!(
+        bincode::deserialize::<ProofDebugInfo<Fr>>(&bytes).unwrap(),
+        info
+    );
+
+    let compressed = proof.compress(&pp).unwrap().debug_dump().unwrap();
+    assert!(compressed.compressed);
+    assert_eq!(compressed.num_steps, num_steps);
+    assert_eq!(compressed.output_arity, None);
+    assert_ne!(compressed.snark_digest, info.snark_digest);
+}
+
+#[test]
+#[ignore]
+fn test_verify_folding_mode_mismatch() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang);
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+
+    // a proof claiming another folding mode is rejected with an error instead
+    // of being reported as invalid
+    let Proof::Compressed(p, n, FoldingMode::IVC, d) = proof.compress(&pp).unwrap() else {
+        panic!("expected a compressed IVC proof");
+    };
+    let retagged = Proof::Compressed(p, n, FoldingMode::NIVC, d);
+    let mismatch = |e| {
+        matches!(
+            e,
+            ProofError::FoldingModeMismatch {
+                expected: FoldingMode::IVC,
+                got: FoldingMode::NIVC,
+            }
+        )
+    };
+    assert!(mismatch(retagged.verify(&pp, &z0, &zi).unwrap_err()));
+    assert!(mismatch(
+        retagged
+            .verify_compressed(&pp.verifier_only(), &z0, &zi)
+            .unwrap_err()
+    ));
+}
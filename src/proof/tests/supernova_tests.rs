@@ -0,0 +1,1162 @@
+use abomonation::Abomonation;
+use bellpepper::util_cs::{metric_cs::MetricCS, Comparable};
+use bellpepper_core::Circuit;
+use ff::{Field, PrimeField};
+use halo2curves::bn256::Fr as Bn;
+use nova::{
+    supernova::{
+        error::SuperNovaError, CircuitDigests, NonUniformCircuit, StepCircuit as SuperStepCircuit,
+    },
+    traits::Engine,
+};
+use pasta_curves::pallas::Scalar as Fr;
+use std::sync::Arc;
+
+use crate::{
+    config::DEFAULT_MAX_COPROCESSORS,
+    coprocessor::{test::DumbCoprocessor, CoCircuit, Coprocessor},
+    error::{LangError, ProofError},
+    eval::lang::{Coproc, Lang},
+    lem::{eval::EvalConfig, pointers::Ptr, store::Store},
+    proof::{
+        compare_folding_modes, extend_digest,
+        nova::{CurveCycleEquipped, C1LEM, E1, E2},
+        supernova::{
+            check_coprocessor_count, check_lang_digests, circuit_cache_key, circuit_cache_keys,
+            circuit_shape_snapshot, nivc_set_digest, public_params,
+            public_params_with_max_coprocessors, secondary_circuit_is_invariant, FoldingConfig,
+            Proof, PublicParams, SharedPublicParams, SuperNovaProver,
+        },
+        FoldingMode, FoldingPath, FrameTrace, Prover, ProvingSettings, RecursiveSNARKTrait,
+        VerifyOutcome,
+    },
+    state::user_sym,
+};
+
+fn dumb_lang() -> Arc<Lang<Fr, DumbCoprocessor<Fr>>> {
+    let mut lang = Lang::new();
+    lang.add_coprocessor(user_sym("cproc-dumb"), DumbCoprocessor::new());
+    Arc::new(lang)
+}
+
+fn prove_nivc<'a, C: Coprocessor<Fr> + 'a>(
+    s: &'a Store<Fr>,
+    lang: &Arc<Lang<Fr, C>>,
+    rc: usize,
+    src: &str,
+) -> (
+    PublicParams<Fr, C1LEM<'a, Fr, C>>,
+    Proof<'a, Fr, C>,
+    Vec<Fr>,
+    Vec<Fr>,
+) {
+    let expr = s.read_with_default_state(src).unwrap();
+    let prover = SuperNovaProver::<'a, Fr, C>::new(rc, lang.clone());
+    let pp = public_params(rc, lang.clone()).unwrap();
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+    (pp, proof, z0, zi)
+}
+
+#[test]
+fn test_proving_types_are_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SuperNovaProver<'_, Fr, Coproc<Fr>>>();
+    assert_send_sync::<Proof<'_, Fr, Coproc<Fr>>>();
+    assert_send_sync::<PublicParams<Fr, C1LEM<'_, Fr, Coproc<Fr>>>>();
+    assert_send_sync::<SharedPublicParams<Fr, C1LEM<'_, Fr, Coproc<Fr>>>>();
+}
+
+#[test]
+fn test_can_compress() {
+    assert!(Proof::<'_, Fr, Coproc<Fr>>::can_compress());
+    assert!(crate::proof::nova::Proof::<'_, Fr, Coproc<Fr>>::can_compress());
+}
+
+#[test]
+#[ignore]
+fn test_concurrent_verification_with_shared_params() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let (pp, proof, z0, zi) = prove_nivc(s, &lang, 1, "(+ 1 2)");
+    let pp = SharedPublicParams::from(pp);
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            let (pp, proof, z0, zi) = (pp.clone(), &proof, &z0, &zi);
+            scope.spawn(move || assert!(proof.verify(&pp, z0, zi).unwrap()));
+        }
+    });
+}
+
+#[test]
+#[ignore]
+fn test_claimed_io() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let (pp, proof, z0, zi) = prove_nivc(s, &lang, 2, "(+ 1 2)");
+    assert_eq!(proof.claimed_input(), &z0);
+    assert_eq!(proof.claimed_output(), &zi);
+
+    let proof = proof.compress(&pp).unwrap();
+    assert_eq!(proof.claimed_input(), &z0);
+    assert_eq!(proof.claimed_output(), &zi);
+    assert!(proof
+        .verify(&pp, proof.claimed_input(), proof.claimed_output())
+        .unwrap());
+}
+
+#[test]
+fn test_prover_from_folding_config() {
+    let lang = dumb_lang();
+    let config = Arc::new(FoldingConfig::new_nivc(lang.clone(), 3));
+    let prover =
+        SuperNovaProver::<'_, Fr, DumbCoprocessor<Fr>>::from_folding_config(config).unwrap();
+    assert_eq!(prover.reduction_count(), 3);
+    assert!(Arc::ptr_eq(prover.lang(), &lang));
+    assert_eq!(prover.folding_mode(), &FoldingMode::NIVC);
+
+    let config = prover.folding_config();
+    assert!(matches!(&*config, FoldingConfig::NIVC(l, 3) if Arc::ptr_eq(l, &lang)));
+}
+
+#[test]
+fn test_prover_from_ivc_folding_config() {
+    let config = Arc::new(FoldingConfig::new_ivc(dumb_lang(), 3));
+    assert!(matches!(
+        SuperNovaProver::<'_, Fr, DumbCoprocessor<Fr>>::from_folding_config(config),
+        Err(ProofError::FoldingModeMismatch {
+            expected: FoldingMode::NIVC,
+            got: FoldingMode::IVC
+        })
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_prover_from_auto_folding_config() {
+    let s = &Store::<Fr>::default();
+    let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+    let config = Arc::new(FoldingConfig::auto(lang.clone(), 1));
+    assert_eq!(config.folding_mode(), FoldingMode::IVC);
+    let prover = SuperNovaProver::<'_, Fr, Coproc<Fr>>::from_folding_config(config).unwrap();
+    assert_eq!(prover.reduction_count(), 1);
+    assert!(Arc::ptr_eq(prover.lang(), &lang));
+
+    let pp = public_params(1, lang).unwrap();
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+    assert_eq!(zi[..2], s.to_scalar_vector(&[s.num_u64(3)]));
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+}
+
+#[test]
+fn test_auto_folding_config() {
+    let lang = dumb_lang();
+    let config = FoldingConfig::auto(lang.clone(), 3);
+    assert!(matches!(&config, FoldingConfig::NIVC(l, 3) if Arc::ptr_eq(l, &lang)));
+    assert_eq!(config.folding_mode(), FoldingMode::NIVC);
+
+    let config = FoldingConfig::auto(Arc::new(Lang::<Fr, Coproc<Fr>>::new()), 3);
+    assert!(matches!(&config, FoldingConfig::IVC(_, 3)));
+    assert_eq!(config.folding_mode(), FoldingMode::IVC);
+}
+
+#[test]
+fn test_check_coprocessor_count() {
+    let lang = dumb_lang();
+    assert!(check_coprocessor_count(&lang, 1).is_ok());
+    assert!(matches!(
+        check_coprocessor_count(&lang, 0),
+        Err(ProofError::TooManyCircuits { count: 1, max: 0 })
+    ));
+    // the limit of the setup can be set instead of taken from the Lurk config
+    assert!(matches!(
+        public_params_with_max_coprocessors(1, lang, 0),
+        Err(ProofError::TooManyCircuits { count: 1, max: 0 })
+    ));
+}
+
+fn test_serde_roundtrip_aux<F: CurveCycleEquipped>()
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    let s = &Store::<F>::default();
+    let lang = Arc::new(Lang::<F, Coproc<F>>::new());
+    let expr = s.read_with_default_state("(+ 1 2)").unwrap();
+    let prover = SuperNovaProver::<'_, F, Coproc<F>>::new(1, lang.clone());
+    let pp = public_params(1, lang).unwrap();
+    let (proof, z0, zi, _num_steps) = prover
+        .evaluate_and_prove(&pp, expr, s.intern_empty_env(), s, 1000)
+        .unwrap();
+
+    let roundtrip = |proof: &Proof<'_, F, Coproc<F>>| {
+        let bytes = bincode::serialize(proof).unwrap();
+        let deserialized: Proof<'_, F, Coproc<F>> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bytes, bincode::serialize(&deserialized).unwrap());
+        assert_eq!(proof.program_counters(), deserialized.program_counters());
+        assert_eq!(proof.claimed_input(), deserialized.claimed_input());
+        assert_eq!(proof.claimed_output(), deserialized.claimed_output());
+        assert!(deserialized.verify(&pp, &z0, &zi).unwrap());
+    };
+
+    roundtrip(&proof);
+    let compressed = proof.compress(&pp).unwrap();
+    assert!(matches!(compressed, Proof::Compressed(..)));
+    roundtrip(&compressed);
+}
+
+#[test]
+#[ignore]
+fn test_matches_digests() {
+    let lang = dumb_lang();
+    let rc = 2;
+    let pp = public_params(rc, lang.clone()).unwrap();
+    assert!(pp.matches_digests(&circuit_cache_keys(rc, &lang)));
+
+    // the keys for another reduction count don't match
+    assert!(!pp.matches_digests(&circuit_cache_keys(rc + 1, &lang)));
+
+    // neither do the keys of another set of circuits
+    let default_lang = Arc::new(Lang::<Fr, DumbCoprocessor<Fr>>::new());
+    assert!(!pp.matches_digests(&circuit_cache_keys(rc, &default_lang)));
+
+    // nor a set with a single tampered digest
+    let mut tampered = (0..lang.coprocessor_count() + 1)
+        .map(|circuit_index| circuit_cache_key(rc, lang.clone(), circuit_index))
+        .collect::<Vec<_>>();
+    tampered[1] += Fr::ONE;
+    assert!(!pp.matches_digests(&CircuitDigests::new(tampered)));
+}
+
+#[test]
+#[ignore]
+fn test_serde_roundtrip_pallas() {
+    test_serde_roundtrip_aux::<Fr>()
+}
+
+#[test]
+#[ignore]
+fn test_serde_roundtrip_bn256() {
+    test_serde_roundtrip_aux::<Bn>()
+}
+
+#[test]
+#[ignore]
+fn test_prove_many() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let prover = SuperNovaProver::<'_, Fr, DumbCoprocessor<Fr>>::new(rc, lang.clone());
+    let pp = public_params(rc, lang.clone()).unwrap();
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+
+    let sources = ["(+ 1 2)", "(cproc-dumb 9 8)", "(* 3 4)"];
+    let mut jobs = Vec::with_capacity(sources.len());
+    let mut outputs = Vec::with_capacity(sources.len());
+    for src in sources {
+        let expr = s.read_with_default_state(src).unwrap();
+        let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+            expr,
+            s.intern_empty_env(),
+            s,
+            1000,
+            &EvalConfig::new_nivc(&lang),
+        )
+        .unwrap();
+        s.hydrate_z_cache();
+        let z0 = s.to_scalar_vector(&frames[0].input);
+        outputs.push(s.to_scalar_vector(&frames.last().unwrap().output));
+        let steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+        jobs.push((z0, steps));
+    }
+    let inputs: Vec<_> = jobs.iter().map(|(z0, _)| z0.clone()).collect();
+
+    // one after the other, two at a time, and all at once
+    for max_parallel_jobs in [1, 2, sources.len()] {
+        let proofs = prover.prove_many(&pp, jobs.clone(), s, max_parallel_jobs);
+        assert_eq!(proofs.len(), sources.len());
+        for ((proof, z0), zi) in proofs.into_iter().zip(&inputs).zip(&outputs) {
+            assert!(proof.unwrap().verify(&pp, z0, zi).unwrap());
+        }
+    }
+
+    // the jobs run in the thread pool of the prover, even a single-threaded one
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let prover = prover.with_thread_pool(Arc::new(pool));
+    let proofs = prover.prove_many(&pp, jobs, s, 1);
+    for ((proof, z0), zi) in proofs.into_iter().zip(&inputs).zip(&outputs) {
+        assert!(proof.unwrap().verify(&pp, z0, zi).unwrap());
+    }
+}
+
+#[test]
+#[ignore]
+fn test_compressed_stats() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let (pp, proof, _z0, _zi) = prove_nivc(s, &lang, 1, "(cproc-dumb 9 8)");
+    assert!(proof.compressed_stats(&pp, &lang).is_none());
+
+    let compressed = proof.compress(&pp).unwrap();
+    let stats = compressed.compressed_stats(&pp, &lang).unwrap();
+    assert!(stats.primary_constraints > 0);
+    assert!(stats.primary_variables > 0);
+    assert!(stats.secondary_constraints > 0);
+    assert!(stats.secondary_variables > 0);
+}
+
+#[test]
+fn test_recommend_reduction_count() {
+    let lang = dumb_lang();
+    let num_constraints = |rc| {
+        let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+        let mut cs = MetricCS::<Fr>::new();
+        C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::blank(folding_config, 0)
+            .synthesize(&mut cs)
+            .unwrap();
+        cs.num_constraints()
+    };
+    let recommend = |target| FoldingMode::NIVC.recommend_reduction_count(&lang, target);
+    let target = num_constraints(3);
+    assert_eq!(recommend(target).unwrap(), 3);
+    assert_eq!(recommend(target - 1).unwrap(), 2);
+    let base = num_constraints(1);
+    assert_eq!(recommend(base).unwrap(), 1);
+
+    // targets below the overhead of the circuit have no reduction count
+    assert!(matches!(
+        recommend(base - 1),
+        Err(ProofError::ConstraintTargetTooLow { min, .. }) if min == base
+    ));
+    assert!(matches!(
+        recommend(0),
+        Err(ProofError::ConstraintTargetTooLow { target: 0, .. })
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_verify_detailed() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let (pp, proof, z0, zi) = prove_nivc(s, &lang, 1, "(cproc-dumb 9 8)");
+    assert!(proof.verify_detailed(&pp, &z0, &zi).is_valid());
+    assert_eq!(proof.folding_mode(), FoldingMode::NIVC);
+    assert_eq!(proof.folding_mode(), pp.folding_mode());
+
+    let mut wrong_zi = zi.clone();
+    wrong_zi[0] += Fr::from(1);
+    match proof.verify_detailed(&pp, &z0, &wrong_zi) {
+        VerifyOutcome::PrimaryOutputMismatch { expected, got } => {
+            assert_eq!(expected, wrong_zi);
+            assert_eq!(got, zi);
+        }
+        outcome => panic!("unexpected outcome: {outcome:?}"),
+    }
+    assert!(!proof.verify(&pp, &z0, &wrong_zi).unwrap());
+
+    // the early exit agrees with the full verification
+    assert_eq!(proof.unverified_output(), Some(&zi[..]));
+    assert!(proof.verify_early_exit(&pp, &z0, &zi).unwrap());
+    assert!(!proof.verify_early_exit(&pp, &z0, &wrong_zi).unwrap());
+
+    // a truncated output is rejected upfront instead of failing the comparison
+    assert!(matches!(
+        proof.verify(&pp, &z0, &zi[..5]),
+        Err(ProofError::ArityMismatch {
+            expected: 6,
+            z0: 6,
+            zi: 5
+        })
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_prove_recursively_supernova_error() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let expr = s.read_with_default_state("(cproc-dumb 9 8)").unwrap();
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_nivc(&lang),
+    )
+    .unwrap();
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+    let steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+    let pp = public_params(rc, lang.clone()).unwrap();
+
+    // SuperNova rejects an initial input of the wrong arity, which is reported
+    // instead of panicking
+    let error = Proof::prove_recursively(&pp, &z0[..4], steps, s, rc, lang).unwrap_err();
+    let source = error.supernova_source().expect("not a SuperNova error");
+    assert!(std::error::Error::source(&error)
+        .and_then(|e| e.downcast_ref::<SuperNovaError>())
+        .is_some_and(|e| e.to_string() == source.to_string()));
+}
+
+#[test]
+#[ignore]
+fn test_prove_recursively_lazy() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let expr = s.read_with_default_state("(cproc-dumb 9 8)").unwrap();
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_nivc(&lang),
+    )
+    .unwrap();
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+    let steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+    let pp = public_params(rc, lang.clone()).unwrap();
+
+    let proof = Proof::prove_recursively_lazy(
+        &pp,
+        &z0,
+        steps.clone().into_iter().map(Ok),
+        s,
+        rc,
+        lang.clone(),
+    )
+    .unwrap();
+    assert_eq!(proof.program_counters(), &[0, 0, 0, 1, 0]);
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+
+    // skipping the step whose circuit selects the coprocessor one breaks the
+    // chain of program counters
+    let mut skipping_steps = steps.clone();
+    skipping_steps.remove(2);
+    assert!(matches!(
+        Proof::prove_recursively_lazy(
+            &pp,
+            &z0,
+            skipping_steps.into_iter().map(Ok),
+            s,
+            rc,
+            lang.clone()
+        ),
+        Err(ProofError::PcInconsistency { step_index: 2 })
+    ));
+
+    // an error yielded by the steps aborts proving
+    let failing_steps = steps
+        .into_iter()
+        .map(Ok)
+        .take(2)
+        .chain(std::iter::once(Err(ProofError::NoSteps)));
+    assert!(Proof::prove_recursively_lazy(&pp, &z0, failing_steps, s, rc, lang.clone()).is_err());
+
+    // steps built for another `Lang` are rejected before being folded
+    let mut other = (*lang).clone();
+    other.add_coprocessor(user_sym("cproc-dumb-2"), DumbCoprocessor::new());
+    let other_config = Arc::new(FoldingConfig::new_nivc(Arc::new(other), rc));
+    let other_steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &other_config);
+    assert!(matches!(
+        Proof::prove_recursively_lazy(
+            &pp,
+            &z0,
+            other_steps.into_iter().map(Ok),
+            s,
+            rc,
+            lang.clone()
+        ),
+        Err(ProofError::LangDigestMismatch { step_index: 0 })
+    ));
+
+    let no_steps = std::iter::empty::<Result<C1LEM<'_, Fr, DumbCoprocessor<Fr>>, ProofError>>();
+    assert!(matches!(
+        Proof::prove_recursively_lazy(&pp, &z0, no_steps, s, rc, lang),
+        Err(ProofError::NoSteps)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_prove_recursively_with_stats() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let expr = s.read_with_default_state("(cproc-dumb 9 8)").unwrap();
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_nivc(&lang),
+    )
+    .unwrap();
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+    let mut steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+    steps[0].cache_witness(s).unwrap();
+    let pp = public_params(rc, lang.clone()).unwrap();
+
+    let settings = |folding_path| ProvingSettings {
+        folding_path,
+        ..Default::default()
+    };
+    let (proof, stats) = Proof::prove_recursively_with_stats_and_settings(
+        &settings(FoldingPath::Sequential),
+        &pp,
+        &z0,
+        steps.clone(),
+        s,
+        rc,
+        lang.clone(),
+        None,
+    )
+    .unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+    assert_eq!(stats.num_steps, 5);
+    assert_eq!(stats.pc_histogram, [(0, 4), (1, 1)].into_iter().collect());
+    assert_eq!(stats.per_step_durations.len(), 5);
+    assert!(stats.estimated_cpu_time >= stats.per_step_durations.iter().sum());
+    assert_eq!((stats.cache_hits, stats.cache_misses), (1, 4));
+    assert!(stats.peak_bytes > 0);
+    assert_eq!(stats.witness_sizes.len(), 5);
+    assert_eq!(stats.folding_path, FoldingPath::Sequential);
+
+    // the parallel path caches witnesses ahead of folding
+    let (proof, parallel_stats) = Proof::prove_recursively_with_stats_and_settings(
+        &settings(FoldingPath::Parallel),
+        &pp,
+        &z0,
+        steps,
+        s,
+        rc,
+        lang.clone(),
+        None,
+    )
+    .unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+    assert_eq!(parallel_stats.folding_path, FoldingPath::Parallel);
+    assert_eq!(parallel_stats.pc_histogram, stats.pc_histogram);
+    assert_eq!(parallel_stats.witness_sizes, stats.witness_sizes);
+    assert!(parallel_stats.cache_hits >= 1);
+    assert_eq!(parallel_stats.cache_hits + parallel_stats.cache_misses, 5);
+    let max_size = *stats.witness_sizes.iter().max().unwrap();
+    let (largest, _) = stats
+        .witness_sizes
+        .iter()
+        .enumerate()
+        .find(|(_, size)| **size == max_size)
+        .unwrap();
+
+    // capping the witness size at the largest witness fails on it
+    let steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+    let capped = Some(max_size - 1);
+    let result = Proof::prove_recursively_with_witness_cap(
+        &pp,
+        &z0,
+        steps.clone(),
+        s,
+        rc,
+        lang.clone(),
+        capped,
+    );
+    assert!(matches!(
+        result,
+        Err(ProofError::WitnessTooLarge { step_index, size })
+            if step_index == largest && size == max_size
+    ));
+
+    // capping the witness size forces the sequential path
+    let (proof, stats) = Proof::prove_recursively_with_stats_and_settings(
+        &settings(FoldingPath::Parallel),
+        &pp,
+        &z0,
+        steps,
+        s,
+        rc,
+        lang,
+        Some(max_size),
+    )
+    .unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+    assert_eq!(stats.folding_path, FoldingPath::Sequential);
+}
+
+#[test]
+#[ignore]
+fn test_prove_recursively_with_dispatch_hook() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let expr = s.read_with_default_state("(cproc-dumb 9 8)").unwrap();
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_nivc(&lang),
+    )
+    .unwrap();
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+    let steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+    let pp = public_params(rc, lang.clone()).unwrap();
+
+    let mut events = vec![];
+    let proof =
+        Proof::prove_recursively_with_dispatch_hook(&pp, &z0, steps, s, rc, lang, |i, pc| {
+            events.push((i, pc))
+        })
+        .unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+    // the only coprocessor call is folded at the fourth step
+    assert_eq!(events, [(3, 1)]);
+}
+
+#[test]
+#[ignore]
+fn test_prove_recursively_with_allowlist() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let expr = s.read_with_default_state("(cproc-dumb 9 8)").unwrap();
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_nivc(&lang),
+    )
+    .unwrap();
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+    let steps = || C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+    let pp = public_params(rc, lang.clone()).unwrap();
+
+    let lurk_only = [0].into_iter().collect();
+    let result = Proof::prove_recursively_with_allowlist(
+        &pp,
+        &z0,
+        steps(),
+        s,
+        rc,
+        lang.clone(),
+        Some(&lurk_only),
+    );
+    assert!(matches!(
+        result,
+        Err(ProofError::DisallowedCoprocessor {
+            pc: 1,
+            step_index: 3
+        })
+    ));
+
+    let with_cproc = [0, 1].into_iter().collect();
+    let proof =
+        Proof::prove_recursively_with_allowlist(&pp, &z0, steps(), s, rc, lang, Some(&with_cproc))
+            .unwrap();
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_prove_to_writer() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let expr = s.read_with_default_state("(cproc-dumb 9 8)").unwrap();
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_nivc(&lang),
+    )
+    .unwrap();
+    s.hydrate_z_cache();
+    let z0 = s.to_scalar_vector(&frames[0].input);
+    let zi = s.to_scalar_vector(&frames.last().unwrap().output);
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+    let steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+    let pp = public_params(rc, lang.clone()).unwrap();
+
+    let prover = SuperNovaProver::<'_, Fr, DumbCoprocessor<Fr>>::new(rc, lang);
+    let mut bytes = vec![];
+    prover
+        .prove_to_writer(&pp, &z0, steps, s, &mut bytes)
+        .unwrap();
+    let proof: Proof<'_, Fr, DumbCoprocessor<Fr>> = bincode::deserialize_from(&bytes[..]).unwrap();
+    assert_eq!(proof.claimed_output(), zi);
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_reproduce_and_compare() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), rc));
+    let steps_for = |src| {
+        let expr = s.read_with_default_state(src).unwrap();
+        let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+            expr,
+            s.intern_empty_env(),
+            s,
+            1000,
+            &EvalConfig::new_nivc(&lang),
+        )
+        .unwrap();
+        s.hydrate_z_cache();
+        let z0 = s.to_scalar_vector(&frames[0].input);
+        let steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+        (z0, steps)
+    };
+    let pp = public_params(rc, lang.clone()).unwrap();
+    let (z0, steps) = steps_for("(cproc-dumb 9 8)");
+    let proof = Proof::prove_recursively(&pp, &z0, steps.clone(), s, rc, lang.clone()).unwrap();
+
+    assert!(
+        Proof::reproduce_and_compare(&proof, &pp, &z0, steps.clone(), s, rc, lang.clone()).unwrap()
+    );
+    let compressed = proof.compress(&pp).unwrap();
+    assert!(
+        Proof::reproduce_and_compare(&compressed, &pp, &z0, steps, s, rc, lang.clone()).unwrap()
+    );
+
+    let (other_z0, other_steps) = steps_for("(cproc-dumb 9 9)");
+    assert!(
+        !Proof::reproduce_and_compare(&compressed, &pp, &other_z0, other_steps, s, rc, lang)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_frame_trace_rebuild() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let expr = s.read_with_default_state("(+ 1 (cproc-dumb 9 8))").unwrap();
+    let eval_config = EvalConfig::new_nivc(&lang);
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &eval_config,
+    )
+    .unwrap();
+    let trace = FrameTrace::new(&frames, s);
+    assert_eq!(trace.len(), frames.len());
+    assert_eq!(trace.z0().unwrap(), s.to_scalar_vector(&frames[0].input));
+
+    let bytes = bincode::serialize(&trace).unwrap();
+    let trace: FrameTrace<Fr> = bincode::deserialize(&bytes).unwrap();
+
+    let s2 = &Store::<Fr>::default();
+    let rebuilt = trace.rebuild_frames(s2, &eval_config).unwrap();
+    assert_eq!(rebuilt.len(), frames.len());
+    for (frame, rebuilt) in frames.iter().zip(rebuilt.iter()) {
+        assert_eq!(frame.pc, rebuilt.pc);
+        assert_eq!(
+            s.to_scalar_vector(&frame.output),
+            s2.to_scalar_vector(&rebuilt.output)
+        );
+    }
+}
+
+#[test]
+#[ignore]
+fn test_prove_from_trace() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let rc = 1;
+    let expr = s.read_with_default_state("(cproc-dumb 9 8)").unwrap();
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        1000,
+        &EvalConfig::new_nivc(&lang),
+    )
+    .unwrap();
+    let trace = FrameTrace::new(&frames, s);
+
+    let s2 = &Store::<Fr>::default();
+    let prover = SuperNovaProver::<'_, Fr, DumbCoprocessor<Fr>>::new(rc, lang.clone());
+    let pp = public_params(rc, lang).unwrap();
+    let (proof, z0, zi, _num_steps) = prover.prove_from_trace(&pp, &trace, s2).unwrap();
+    assert_eq!(Some(z0.clone()), trace.z0());
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+}
+
+/// A coprocessor whose evaluation arity doesn't match its circuit arity
+#[derive(Clone, Debug)]
+struct MismatchedArity;
+
+impl CoCircuit<Fr> for MismatchedArity {
+    fn arity(&self) -> usize {
+        2
+    }
+}
+
+impl Coprocessor<Fr> for MismatchedArity {
+    fn eval_arity(&self) -> usize {
+        1
+    }
+
+    fn evaluate_simple(&self, s: &Store<Fr>, _args: &[Ptr]) -> Ptr {
+        s.intern_nil()
+    }
+}
+
+#[test]
+fn test_validate_circuit_mapping() {
+    assert!(dumb_lang().validate_circuit_mapping().is_ok());
+
+    let mut lang = Lang::<Fr, MismatchedArity>::new();
+    lang.add_coprocessor(user_sym("cproc-mismatched"), MismatchedArity);
+    match lang.validate_circuit_mapping() {
+        Err(LangError::CircuitArityMismatch {
+            name,
+            eval_arity,
+            circuit_arity,
+        }) => {
+            assert_eq!(name, user_sym("cproc-mismatched").to_string());
+            assert_eq!((eval_arity, circuit_arity), (1, 2));
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+    assert!(matches!(
+        public_params::<'_, Fr, MismatchedArity>(1, Arc::new(lang)),
+        Err(ProofError::Lang(_))
+    ));
+}
+
+#[test]
+fn test_circuit_cache_key_cached() {
+    let lang = dumb_lang();
+    let key = |lang: &Arc<Lang<Fr, DumbCoprocessor<Fr>>>, rc, i| {
+        circuit_cache_key::<'_, Fr, DumbCoprocessor<Fr>>(rc, lang.clone(), i)
+    };
+    let key0 = key(&lang, 1, 0);
+    let key1 = key(&lang, 1, 1);
+    assert_ne!(key0, key1);
+    assert_eq!(key(&lang, 2, 1), Fr::from(2) * key1);
+
+    // a fresh `Lang` computes the same keys
+    let mut fresh = Lang::new();
+    fresh.add_coprocessor(user_sym("cproc-dumb"), DumbCoprocessor::new());
+    assert_eq!(key(&Arc::new(fresh), 1, 1), key1);
+
+    // adding a coprocessor resets the digests cached in a clone
+    let mut extended = (*lang).clone();
+    extended.add_coprocessor(user_sym("cproc-dumb-2"), DumbCoprocessor::new());
+    let extended = Arc::new(extended);
+    assert_ne!(key(&extended, 1, 0), key0);
+    key(&extended, 1, 2);
+}
+
+#[test]
+fn test_secondary_circuit_is_invariant() {
+    // the provers build the secondary circuit once per proof for the supported
+    // curve cycles
+    assert!(secondary_circuit_is_invariant::<Fr>());
+    assert!(secondary_circuit_is_invariant::<Bn>());
+}
+
+#[test]
+fn test_check_lang_digests() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let expr = s.read_with_default_state("(cproc-dumb 1 2)").unwrap();
+    let frames = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::build_frames(
+        expr,
+        s.intern_empty_env(),
+        s,
+        100,
+        &EvalConfig::new_nivc(&lang),
+    )
+    .unwrap();
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang.clone(), 1));
+    let steps = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::from_frames(&frames, s, &folding_config);
+    assert!(check_lang_digests(&steps, &lang).is_ok());
+
+    // an equal `Lang` has the same digest
+    assert!(check_lang_digests(&steps, &dumb_lang()).is_ok());
+
+    let mut other = (*lang).clone();
+    other.add_coprocessor(user_sym("cproc-dumb-2"), DumbCoprocessor::new());
+    assert!(matches!(
+        check_lang_digests(&steps, &Arc::new(other)),
+        Err(ProofError::LangDigestMismatch { step_index: 0 })
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_compare_folding_modes() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let src = "(+ 1 (cproc-dumb 9 8))";
+    let comparison = compare_folding_modes(&lang, 1, src, s).unwrap();
+    for (mode, cost) in [
+        (FoldingMode::IVC, &comparison.ivc),
+        (FoldingMode::NIVC, &comparison.nivc),
+    ] {
+        assert_eq!(cost.num_steps, mode.count_steps(s, src, 1, &lang).unwrap());
+        assert!(cost.proof_bytes > 0);
+    }
+}
+
+#[test]
+fn test_circuit_shape_snapshot() {
+    let lang = dumb_lang();
+    let snapshot = circuit_shape_snapshot::<'_, Fr, DumbCoprocessor<Fr>>(1, &lang);
+    assert_eq!(snapshot.reduction_count, 1);
+    assert_eq!(snapshot.circuits.len(), lang.coprocessor_count() + 1);
+    assert_eq!(
+        snapshot,
+        circuit_shape_snapshot::<'_, Fr, DumbCoprocessor<Fr>>(1, &lang)
+    );
+    assert_eq!(snapshot.to_string().lines().count(), 3);
+
+    // only the Lurk step circuit depends on the reduction count
+    let other = circuit_shape_snapshot::<'_, Fr, DumbCoprocessor<Fr>>(2, &lang);
+    assert!(other.circuits[0].num_constraints > snapshot.circuits[0].num_constraints);
+    assert_ne!(other.circuits[0].digest, snapshot.circuits[0].digest);
+    assert_eq!(other.circuits[1..], snapshot.circuits[1..]);
+}
+
+#[test]
+fn test_nivc_set_digest() {
+    let lang = dumb_lang();
+    let digest = nivc_set_digest::<'_, Fr, DumbCoprocessor<Fr>>(1, &lang);
+    assert_eq!(
+        digest,
+        circuit_cache_keys::<'_, Fr, DumbCoprocessor<Fr>>(1, &lang).digest()
+    );
+    assert_ne!(
+        digest,
+        nivc_set_digest::<'_, Fr, DumbCoprocessor<Fr>>(2, &lang)
+    );
+}
+
+#[test]
+#[ignore]
+fn test_prove_source_error() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let prover = SuperNovaProver::<'_, Fr, DumbCoprocessor<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang).unwrap();
+
+    let (proof, output) = prover.prove_source(&pp, s, "(/ 21 0)", None).unwrap();
+    assert_eq!(output, s.num_u64(0));
+    let (z0, zi) = (proof.claimed_input(), proof.claimed_output());
+    assert_eq!(zi[4..], s.to_scalar_vector(&[s.cont_error()]));
+    assert!(proof.verify(&pp, z0, zi).unwrap());
+    assert!(proof.verify_error(&pp, z0, zi).unwrap());
+
+    // a computation that terminates normally doesn't prove an error
+    let (proof, _) = prover.prove_source(&pp, s, "(/ 21 7)", None).unwrap();
+    let (z0, zi) = (proof.claimed_input(), proof.claimed_output());
+    assert!(proof.verify(&pp, z0, zi).unwrap());
+    assert!(!proof.verify_error(&pp, z0, zi).unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_prove_source_with_stack_size() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let prover = SuperNovaProver::<'_, Fr, DumbCoprocessor<Fr>>::new(10, lang.clone())
+        .with_stack_size(64 * 1024 * 1024);
+    let pp = public_params(10, lang).unwrap();
+
+    // a deeply nested list, quoted to keep the evaluation short
+    let depth = 5000;
+    let src = format!("(quote {}0{})", "(1 ".repeat(depth), ")".repeat(depth));
+    let (proof, output) = prover.prove_source(&pp, s, &src, None).unwrap();
+    assert_eq!(
+        output,
+        s.read_with_default_state(&src[7..src.len() - 1]).unwrap()
+    );
+    assert!(proof
+        .verify(&pp, proof.claimed_input(), proof.claimed_output())
+        .unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_prove_source() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+    let prover = SuperNovaProver::<'_, Fr, DumbCoprocessor<Fr>>::new(1, lang.clone());
+    let pp = public_params(1, lang).unwrap();
+
+    let (proof, output) = prover
+        .prove_source(&pp, s, "(cproc-dumb 9 8)", None)
+        .unwrap();
+    assert_eq!(output, s.num_u64(89));
+    assert!(proof
+        .verify(&pp, proof.claimed_input(), proof.claimed_output())
+        .unwrap());
+
+    assert!(prover.prove_source(&pp, s, "(cproc-dumb 9", None).is_err());
+
+    // the evaluation takes 5 reductions
+    assert!(prover
+        .prove_source(&pp, s, "(cproc-dumb 9 8)", Some(5))
+        .is_ok());
+    assert!(matches!(
+        prover.prove_source(&pp, s, "(cproc-dumb 9 8)", Some(4)),
+        Err(ProofError::EvaluationLimitExceeded { limit: 4 })
+    ));
+    let diverging = "(letrec ((loop (lambda (x) (loop x)))) (loop 0))";
+    assert!(matches!(
+        prover.prove_source(&pp, s, diverging, Some(100)),
+        Err(ProofError::EvaluationLimitExceeded { limit: 100 })
+    ));
+
+    // sources can refer to the bindings of a predefined environment
+    let env = s.push_binding(
+        s.intern_user_symbol("y"),
+        s.num_u64(8),
+        s.intern_empty_env(),
+    );
+    let (proof, output) = prover
+        .prove_source_with_env(&pp, s, "(cproc-dumb 9 y)", env, s.cont_outermost(), None)
+        .unwrap();
+    assert_eq!(output, s.num_u64(89));
+    let expr = s.read_with_default_state("(cproc-dumb 9 y)").unwrap();
+    let z0 = s.to_scalar_vector(&[expr, env, s.cont_outermost()]);
+    assert_eq!(proof.claimed_input(), z0);
+    assert!(proof
+        .verify(&pp, proof.claimed_input(), proof.claimed_output())
+        .unwrap());
+    assert!(matches!(
+        prover.prove_source_with_env(&pp, s, "y", s.num_u64(8), s.cont_outermost(), None),
+        Err(ProofError::InvalidInitialEnv)
+    ));
+}
+
+#[test]
+fn test_extend_digest() {
+    let digest = Fr::from(42);
+    let extended = extend_digest(digest, b"v1.0");
+    assert_eq!(extend_digest(digest, b"v1.0"), extended);
+    assert_ne!(extend_digest(digest, b""), extended);
+    assert_ne!(extend_digest(digest, b"v1.1"), extended);
+    assert_ne!(extend_digest(Fr::from(43), b"v1.0"), extended);
+    // the length of `extra` tells trailing zeros apart
+    assert_ne!(extend_digest(digest, b"v1.0\0"), extended);
+}
+
+#[test]
+fn test_single_coprocessor_circuits() {
+    let lang = dumb_lang();
+    assert_eq!(lang.coprocessor_count(), 1);
+    let folding_config = Arc::new(FoldingConfig::new_nivc(lang, 1));
+    let circuit = C1LEM::<'_, Fr, DumbCoprocessor<Fr>>::blank(folding_config, 0);
+    assert_eq!(circuit.num_circuits(), 2);
+    let lurk_circuit = circuit.primary_circuit(0);
+    let cproc_circuit = circuit.primary_circuit(1);
+    assert_eq!(lurk_circuit.circuit_index(), 0);
+    assert_eq!(cproc_circuit.circuit_index(), 1);
+    assert_eq!(
+        SuperStepCircuit::arity(&lurk_circuit),
+        SuperStepCircuit::arity(&cproc_circuit)
+    );
+}
+
+#[test]
+#[ignore]
+fn test_prove_single_coprocessor() {
+    let s = &Store::<Fr>::default();
+    let lang = dumb_lang();
+
+    // a single call, surrounded by Lurk reductions
+    let (pp, proof, z0, zi) = prove_nivc(s, &lang, 1, "(+ 1 (cproc-dumb 2 3))");
+    let pcs = proof.program_counters();
+    assert_eq!(pcs.iter().filter(|&&pc| pc == 1).count(), 1);
+    assert!(pcs.iter().all(|&pc| pc <= 1));
+    assert_eq!(pcs.first(), Some(&0));
+    assert_eq!(pcs.last(), Some(&0));
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+    let compressed = proof.compress(&pp).unwrap();
+    assert!(compressed.verify(&pp, &z0, &zi).unwrap());
+
+    // the coprocessor circuit doesn't need to be used at all
+    let (pp, proof, z0, zi) = prove_nivc(s, &lang, 1, "(+ 1 2)");
+    assert!(proof.program_counters().iter().all(|&pc| pc == 0));
+    assert!(proof.verify(&pp, &z0, &zi).unwrap());
+    let compressed = proof.compress(&pp).unwrap();
+    assert!(compressed.verify(&pp, &z0, &zi).unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_from_aux_params() {
+    let lang = dumb_lang();
+    let PublicParams { pp, .. } = public_params(1, lang.clone()).unwrap();
+    let expected = pp.digest();
+    let (mut circuit_shapes, aux) = pp.into_parts();
+    let pp = PublicParams::<Fr, C1LEM<'_, Fr, _>>::from_aux_params(
+        aux.clone(),
+        circuit_shapes.clone(),
+        1,
+        &lang,
+        DEFAULT_MAX_COPROCESSORS,
+    )
+    .unwrap();
+    assert_eq!(pp.digest(), expected);
+
+    // the auxiliary parameters of another reduction count record another digest
+    let PublicParams { pp: other, .. } = public_params(2, lang.clone()).unwrap();
+    let (_, other_aux) = other.into_parts();
+    assert!(matches!(
+        PublicParams::<Fr, C1LEM<'_, Fr, _>>::from_aux_params(
+            other_aux,
+            circuit_shapes.clone(),
+            1,
+            &lang,
+            DEFAULT_MAX_COPROCESSORS,
+        ),
+        Err(ProofError::AuxParamsMismatch)
+    ));
+
+    circuit_shapes.pop();
+    assert!(matches!(
+        PublicParams::<Fr, C1LEM<'_, Fr, _>>::from_aux_params(
+            aux,
+            circuit_shapes,
+            1,
+            &lang,
+            DEFAULT_MAX_COPROCESSORS,
+        ),
+        Err(ProofError::CircuitCountMismatch {
+            expected: 2,
+            got: 1
+        })
+    ));
+}
@@ -1,6 +1,8 @@
 use std::io;
 use thiserror::Error;
 
+use crate::error::ProofError;
+
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum Error {
@@ -10,4 +12,6 @@ pub enum Error {
     Cache(String),
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("Proof error: {0}")]
+    Proof(#[from] ProofError),
 }
@@ -98,6 +98,7 @@ pub enum Kind {
 /// What we put into the cache
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Metadata {
+    pub curve: String,
     pub rc: usize,
     pub lang: String,
     pub abomonated: bool,
@@ -110,6 +111,7 @@ impl Metadata {
         instance: &Instance<'a, F, C>,
     ) -> Self {
         Metadata {
+            curve: F::CURVE_NAME.to_owned(),
             rc: instance.rc,
             lang: instance.lang.clone().key(),
             abomonated: instance.abomonated,
@@ -127,7 +129,7 @@ where
     pub fn new(rc: usize, lang: Arc<Lang<F, C>>, abomonated: bool, kind: Kind) -> Self {
         let cache_key = match kind {
             Kind::NovaPublicParams => nova::circuit_cache_key::<'a, F, C>(rc, lang.clone()),
-            Kind::SuperNovaAuxParams => supernova::circuit_cache_keys::<F, C>(rc, &lang).digest(),
+            Kind::SuperNovaAuxParams => supernova::nivc_set_digest::<F, C>(rc, &lang),
             Kind::SuperNovaCircuitParams(circuit_index) => {
                 supernova::circuit_cache_key::<'a, F, C>(rc, lang.clone(), circuit_index)
             }
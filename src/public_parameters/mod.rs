@@ -147,10 +147,15 @@ where
         );
         let (pk, vk) = CompressedSNARK::setup(&pp).unwrap();
 
-        supernova::PublicParams { pp, pk, vk }
+        supernova::PublicParams {
+            pp,
+            pk,
+            vk,
+            rc: instance_primary.rc,
+        }
     } else {
         println!("generating running claim params");
-        let pp = default(instance_primary);
+        let pp = default(instance_primary)?;
 
         let (circuit_params_vec, aux_params) = pp.pp.into_parts();
 
@@ -167,7 +172,12 @@ where
         );
         let (pk, vk) = CompressedSNARK::setup(&pp).unwrap();
 
-        supernova::PublicParams { pp, pk, vk }
+        supernova::PublicParams {
+            pp,
+            pk,
+            vk,
+            rc: instance_primary.rc,
+        }
     };
 
     Ok(pp)
@@ -177,6 +187,7 @@ where
 mod tests {
     use super::{instance::Kind, *};
     use crate::eval::lang::{Coproc, Lang};
+    use camino::Utf8Path;
     use pasta_curves::pallas::Scalar as S1;
     use tempfile::Builder;
 
@@ -191,6 +202,23 @@ mod tests {
         // Without disk cache, writes to tmpfile
         let _public_params = public_params::<S1, Coproc<S1>>(&instance).unwrap();
         // With disk cache, reads from tmpfile
-        let _public_params = public_params::<S1, Coproc<S1>>(&instance).unwrap();
+        let public_params = public_params::<S1, Coproc<S1>>(&instance).unwrap();
+        assert_eq!(public_params.curve_name(), "pallas/vesta");
+    }
+
+    #[test]
+    fn mmap_public_params() {
+        let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+        let path = Utf8Path::from_path(tmp_dir.path()).unwrap().join("pp");
+
+        let lang: Arc<Lang<S1, Coproc<S1>>> = Arc::new(Lang::new());
+        let pp = nova::public_params::<S1, Coproc<S1>>(10, lang);
+        let mut file = std::fs::File::create(&path).unwrap();
+        unsafe { abomonation::encode(&pp, &mut file).unwrap() };
+
+        let mmapped_pp =
+            unsafe { PublicParams::<S1, C1LEM<'_, S1, Coproc<S1>>>::mmap_from(&path).unwrap() };
+        mmapped_pp.warm_up();
+        assert_eq!(pp.digest(), mmapped_pp.digest());
     }
 }
@@ -0,0 +1,65 @@
+//! Proving and verifying without touching the filesystem, as documented in
+//! "Running in memory" in the `proof` module docs. This runs in a process of its
+//! own, so the counters of the process only see the work of this test.
+
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore]
+fn test_prove_and_verify_in_memory() {
+    use std::{fs::File, os::unix::fs::FileExt, sync::Arc};
+
+    use lurk::{
+        config::{Settings, LURK_CONFIG},
+        eval::lang::{Coproc, Lang},
+        lem::store::Store,
+        proof::{
+            nova::{public_params, NovaProver},
+            Prover, RecursiveSNARKTrait,
+        },
+    };
+    use pasta_curves::pallas::Scalar as Fr;
+
+    // `/proc/self/io` counts the read and write syscalls of all the threads of
+    // the process. Sampling it takes a single `pread`, on a file opened once.
+    let io = File::open("/proc/self/io").expect("I/O accounting is unavailable");
+    let sample = || {
+        let mut buf = [0u8; 512];
+        let n = io.read_at(&mut buf, 0).unwrap();
+        let text = std::str::from_utf8(&buf[..n]).unwrap();
+        let counter = |name: &str| -> u64 {
+            text.lines()
+                .find_map(|line| line.strip_prefix(name))
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap()
+        };
+        (counter("syscr:"), counter("syscw:"))
+    };
+
+    LURK_CONFIG
+        .set(Settings::in_memory())
+        .expect("the config was already set");
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build_global()
+        .unwrap();
+
+    let before = sample();
+    let calibration = sample();
+    let verified = {
+        let store = Store::<Fr>::default();
+        let lang = Arc::new(Lang::<Fr, Coproc<Fr>>::new());
+        let expr = store.read_with_default_state("(+ 1 2)").unwrap();
+        let prover = NovaProver::<'_, Fr, Coproc<Fr>>::new(1, lang.clone());
+        let pp = public_params(1, lang);
+        let (proof, z0, zi, _num_steps) = prover
+            .evaluate_and_prove(&pp, expr, store.intern_empty_env(), &store, 100)
+            .unwrap();
+        proof.verify(&pp, &z0, &zi).unwrap()
+    };
+    let after = sample();
+    assert!(verified);
+
+    // only the reads of the samples themselves were counted
+    assert_eq!(after.0 - calibration.0, calibration.0 - before.0);
+    assert_eq!(after.1, before.1);
+}